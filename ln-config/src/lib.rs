@@ -1,7 +1,10 @@
+pub mod secrets;
+
 use std::{
     fs::File,
     io::{Read, Write},
     path::PathBuf,
+    process::Command,
     sync::{LazyLock, OnceLock},
 };
 
@@ -21,21 +24,288 @@ pub static CONFIG: LazyLock<Config> = LazyLock::new(|| {
 #[derive(Serialize, Deserialize)]
 pub struct Config {
     pub connection: Connection,
+    /// Additional accounts, selectable with `--profile` or from within the app.
+    #[serde(default)]
+    pub profiles: Vec<Connection>,
     pub general: General,
+    #[serde(default)]
+    pub network: Network,
+    #[serde(default)]
+    pub unreads: Unreads,
+    #[serde(default)]
+    pub feed: Feed,
+    #[serde(default)]
+    pub filters: Filters,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct General {
     #[serde(default)]
     pub accent_color: Color,
+    /// What to do with the thumbnails of NSFW posts.
+    #[serde(default)]
+    pub nsfw: NsfwMode,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NsfwMode {
+    /// Doesn't even fetch them.
+    Hide,
+    /// Pixelates them beyond recognition.
+    #[default]
+    Blur,
+    Show,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Network {
+    /// How many times a failed request is retried before giving up.
+    pub max_retries: u32,
+    /// API requests that may be in flight at the same time.
+    pub max_concurrent_requests: usize,
+    /// API requests started per second at most, searches counted apart as Lemmy
+    /// does. `0` disables the limit.
+    pub requests_per_second: f64,
+    /// Image downloads that may be in flight at the same time.
+    pub max_concurrent_images: usize,
+    /// Image downloads started per second at most, counted per host. `0`
+    /// disables the limit.
+    pub images_per_second: f64,
+    /// Seconds to wait for a connection to be established.
+    pub connect_timeout: u64,
+    /// Seconds to wait for the server between reads of a response.
+    pub read_timeout: u64,
+    /// HTTP(S) or SOCKS5 proxy, like `socks5h://localhost:9050`.
+    pub proxy: Option<String>,
+    /// PEM file with additional certificate authorities to trust.
+    pub ca_bundle: Option<PathBuf>,
+    /// Sent with every request, `lemmynator/<version>` by default.
+    pub user_agent: Option<String>,
+}
+
+impl Default for Network {
+    fn default() -> Self {
+        // Lemmy allows 180 requests per minute by default, stay a bit below that.
+        Self {
+            max_retries: 3,
+            max_concurrent_requests: 4,
+            requests_per_second: 2.5,
+            max_concurrent_images: 6,
+            images_per_second: 10.0,
+            connect_timeout: 10,
+            read_timeout: 30,
+            proxy: None,
+            ca_bundle: None,
+            user_agent: None,
+        }
+    }
+}
+
+/// Overrides for the account's or instance's defaults, which are used otherwise.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Feed {
+    /// The tab shown on startup.
+    pub default_tab: Option<Tab>,
+    /// Sorts as Lemmy names them, like `Hot`, `New` or `TopWeek`.
+    pub subscribed_sort: Option<String>,
+    pub local_sort: Option<String>,
+    pub all_sort: Option<String>,
+    /// Adds a tab with the posts and comments you saved, for accounts only.
+    pub saved_tab: bool,
+    /// Leaves read posts out of listings, the account's setting is used if unset.
+    pub hide_read: Option<bool>,
+    /// Marks posts read once scrolled past, not only when opened.
+    pub mark_read_on_scroll: bool,
+}
+
+impl Feed {
+    pub fn sort(&self, tab: Tab) -> Option<&str> {
+        match tab {
+            Tab::Subscribed => self.subscribed_sort.as_deref(),
+            Tab::Local => self.local_sort.as_deref(),
+            Tab::All => self.all_sort.as_deref(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Tab {
+    Subscribed,
+    Local,
+    All,
+}
+
+/// Posts matching any of these are left out of listings.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Filters {
+    /// Looked for in titles and bodies, regardless of case.
+    pub keywords: Vec<String>,
+    /// Regular expressions matched against titles and bodies.
+    pub regexes: Vec<String>,
+    /// Hosts the posts link to, including their subdomains.
+    pub domains: Vec<String>,
+    /// Authors as `name` or `name@instance`.
+    pub users: Vec<String>,
+    /// Communities as `name` or `name@instance`.
+    pub communities: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Unreads {
+    /// Seconds between checks for new replies, mentions and messages, `0` disables them.
+    pub poll_interval: u64,
+    /// While idle, the interval doubles after every check, up to this many seconds.
+    pub max_poll_interval: u64,
+    /// How new unreads are announced, besides the counter in the top bar.
+    pub notify: UnreadsNotification,
+}
+
+impl Default for Unreads {
+    fn default() -> Self {
+        Self {
+            poll_interval: 60,
+            max_poll_interval: 15 * 60,
+            notify: UnreadsNotification::None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum UnreadsNotification {
+    #[default]
+    None,
+    /// Shows the count in the terminal's title.
+    Title,
+    /// Rings the terminal's bell.
+    Bell,
+    Both,
+}
+
+impl UnreadsNotification {
+    pub fn title(self) -> bool {
+        matches!(self, Self::Title | Self::Both)
+    }
+
+    pub fn bell(self) -> bool {
+        matches!(self, Self::Bell | Self::Both)
+    }
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct Connection {
+    /// Name of the profile, `[connection]` is called "default" unless named otherwise.
+    pub name: Option<String>,
     pub instance: String,
-    pub username: String,
-    // TODO: make it more secure
-    pub password: String,
+    /// Without a username Lemmynator browses the instance anonymously.
+    pub username: Option<String>,
+    /// Plaintext password, prefer one of the other sources below.
+    pub password: Option<String>,
+    /// Shell command printing the password, e.g. `pass show lemmy`.
+    pub password_command: Option<String>,
+    /// Name of an environment variable holding the password.
+    pub password_env: Option<String>,
+    /// File holding the password, which must not be readable by other users.
+    pub password_file: Option<PathBuf>,
+}
+
+const PASSWORD_KEYS: [&str; 4] = [
+    "password",
+    "password_command",
+    "password_env",
+    "password_file",
+];
+
+impl Connection {
+    /// Base URL of the instance, including the scheme.
+    ///
+    /// `instance` can be either a bare hostname (in which case HTTPS is assumed)
+    /// or a full URL such as `http://localhost:8536`.
+    pub fn base_url(&self) -> String {
+        base_url(&self.instance)
+    }
+
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or("default")
+    }
+
+    /// The profile's name, made safe to be used as a file name.
+    ///
+    /// Anything but letters, digits and `-` is escaped as `_` and the hex of
+    /// its bytes, so that no two names share a key.
+    pub fn cache_key(&self) -> String {
+        let mut key = String::new();
+        for c in self.name().chars() {
+            if c.is_alphanumeric() || c == '-' {
+                key.push(c);
+            } else {
+                for byte in c.to_string().bytes() {
+                    key.push_str(&format!("_{byte:02x}"));
+                }
+            }
+        }
+        key
+    }
+
+    /// Name of the cache file holding this profile's token.
+    pub fn jwt_cache_name(&self) -> String {
+        format!("tokens/{}", self.cache_key())
+    }
+
+    pub fn is_anonymous(&self) -> bool {
+        self.username.is_none()
+    }
+
+    /// Resolves the password from the first configured source.
+    pub fn password(&self) -> Result<String> {
+        if let Some(command) = &self.password_command {
+            let output = Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .output()
+                .with_context(|| format!("failed to run password_command `{command}`"))?;
+            if !output.status.success() {
+                bail!("password_command `{command}` exited with {}", output.status);
+            }
+            let stdout = String::from_utf8(output.stdout)
+                .context("password_command printed invalid UTF-8")?;
+            return Ok(stdout.trim_end_matches(['\n', '\r']).to_string());
+        }
+
+        if let Some(var) = &self.password_env {
+            return std::env::var(var)
+                .with_context(|| format!("environment variable {var} is not set"));
+        }
+
+        if let Some(path) = &self.password_file {
+            let password = secrets::read_private_file(path)?;
+            return Ok(password.trim_end_matches(['\n', '\r']).to_string());
+        }
+
+        self.password
+            .clone()
+            .context("no password configured for this account")
+    }
+}
+
+fn base_url(instance: &str) -> String {
+    let instance = instance.trim_end_matches('/');
+    if instance.starts_with("http://") || instance.starts_with("https://") {
+        instance.to_string()
+    } else {
+        format!("https://{instance}")
+    }
+}
+
+/// Checks that `instance` makes a URL with a host, paths of reverse proxies included.
+fn is_valid_instance(instance: &str) -> bool {
+    url::Url::parse(&base_url(instance)).is_ok_and(|url| url.has_host())
 }
 
 const DEFAULT_CONFIG: &str = include_str!("../defaults/config.toml");
@@ -60,21 +330,87 @@ impl Config {
         Ok(config)
     }
 
+    /// Every profile, starting with the one from `[connection]`.
+    pub fn profiles(&self) -> impl Iterator<Item = &Connection> {
+        std::iter::once(&self.connection).chain(&self.profiles)
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&Connection> {
+        self.profiles().find(|profile| profile.name() == name)
+    }
+
     fn table_config_verify(table: &Table) -> Result<()> {
-        let Some(connection_table) = table.get("connection").unwrap().as_table() else {
+        let Some(connection_table) = table
+            .get("connection")
+            .and_then(|connection| connection.as_table())
+        else {
             bail!("expected connection table")
         };
 
-        connection_table
-            .get("username")
-            .and_then(|username| username.as_str())
+        Self::connection_table_verify(connection_table)?;
+
+        let Some(profiles) = table.get("profiles") else {
+            return Ok(());
+        };
+        let Some(profiles) = profiles.as_array() else {
+            bail!("expected profiles to be a list of [[profiles]] tables")
+        };
+
+        let mut names = vec![connection_table
+            .get("name")
+            .and_then(|name| name.as_str())
+            .unwrap_or("default")];
+
+        for profile in profiles {
+            let Some(profile_table) = profile.as_table() else {
+                bail!("expected profiles to be a list of [[profiles]] tables")
+            };
+
+            let name = profile_table
+                .get("name")
+                .and_then(|name| name.as_str())
+                .context("every entry in [[profiles]] needs a name")?;
+
+            if names.contains(&name) {
+                bail!("there's more than one profile called \"{name}\"");
+            }
+            names.push(name);
+
+            Self::connection_table_verify(profile_table)
+                .with_context(|| format!("in profile \"{name}\""))?;
+        }
+
+        Ok(())
+    }
+
+    fn connection_table_verify(connection_table: &Table) -> Result<()> {
+        let instance = connection_table
+            .get("instance")
+            .and_then(|instance| instance.as_str())
             .with_context(|| {
                 format!(
-                    "no username in {}",
+                    "no instance in {}",
                     Self::get_config_path().to_str().unwrap()
                 )
             })?;
 
+        if !is_valid_instance(instance) {
+            bail!("\"{instance}\" is not a valid instance address");
+        }
+
+        let has_username = connection_table.get("username").is_some();
+        let has_password = PASSWORD_KEYS
+            .iter()
+            .any(|key| connection_table.contains_key(*key));
+
+        if has_username && !has_password {
+            bail!(
+                "username is set, but there's no password in {} (set one of {})",
+                Self::get_config_path().to_str().unwrap(),
+                PASSWORD_KEYS.join(", ")
+            );
+        }
+
         Ok(())
     }
 
@@ -117,7 +453,7 @@ mod tests {
     fn invalid_config() -> Table {
         toml::toml! {
             [connection]
-            instance = "bad_url"
+            instance = "bad url:port"
         }
     }
 
@@ -134,9 +470,216 @@ mod tests {
         assert!(Config::table_config_verify(&valid_config).is_ok());
     }
 
+    fn connection(instance: &str) -> Connection {
+        Connection {
+            name: None,
+            instance: instance.to_string(),
+            username: None,
+            password: None,
+            password_command: None,
+            password_env: None,
+            password_file: None,
+        }
+    }
+
+    #[test]
+    fn base_url_defaults_to_https() {
+        assert_eq!(connection("lemmy.ml").base_url(), "https://lemmy.ml");
+    }
+
+    #[test]
+    fn base_url_keeps_explicit_scheme() {
+        assert_eq!(
+            connection("http://localhost:8536/").base_url(),
+            "http://localhost:8536"
+        );
+    }
+
     #[test]
     fn invalidates_properly() {
         let invalid_config = invalid_config();
         assert!(Config::table_config_verify(&invalid_config).is_err());
     }
+
+    #[test]
+    fn invalidates_username_without_password() {
+        let config = toml::toml! {
+            [connection]
+            instance = "lemmy.ml"
+            username = "lemmynator"
+        };
+        assert!(Config::table_config_verify(&config).is_err());
+    }
+
+    #[test]
+    fn validates_profiles() {
+        let config = toml::toml! {
+            [connection]
+            instance = "lemmy.ml"
+
+            [[profiles]]
+            name = "work"
+            instance = "lemmy.world"
+        };
+        assert!(Config::table_config_verify(&config).is_ok());
+    }
+
+    #[test]
+    fn cache_keys_are_unique() {
+        let named = |name: &str| Connection {
+            name: Some(name.to_string()),
+            ..connection("lemmy.ml")
+        };
+        assert_eq!(named("work-2").cache_key(), "work-2");
+        assert_eq!(named("a b").cache_key(), "a_20b");
+        assert_eq!(named("a_b").cache_key(), "a_5fb");
+        assert_eq!(named("../x").cache_key(), "_2e_2e_2fx");
+        assert_eq!(connection("lemmy.ml").jwt_cache_name(), "tokens/default");
+    }
+
+    #[test]
+    fn invalidates_duplicate_profiles() {
+        let config = toml::toml! {
+            [connection]
+            instance = "lemmy.ml"
+
+            [[profiles]]
+            name = "default"
+            instance = "lemmy.world"
+        };
+        assert!(Config::table_config_verify(&config).is_err());
+    }
+
+    #[test]
+    fn accepts_password_sources() {
+        let config = toml::toml! {
+            [connection]
+            instance = "lemmy.ml"
+            username = "lemmynator"
+            password_command = "pass show lemmy"
+        };
+        assert!(Config::table_config_verify(&config).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn password_command_output_is_used() {
+        let connection = Connection {
+            password_command: Some("echo hunter2".to_string()),
+            ..connection("lemmy.ml")
+        };
+        assert_eq!(connection.password().unwrap(), "hunter2");
+    }
+
+    #[test]
+    fn network_has_defaults() {
+        let config = toml::toml! {
+            [connection]
+            instance = "lemmy.ml"
+
+            [general]
+            accent_color = "LightGreen"
+
+            [network]
+            max_retries = 5
+        };
+        let config = Config::table_to_config(config).unwrap();
+        assert_eq!(config.network.max_retries, 5);
+        assert_eq!(config.network.max_concurrent_requests, 4);
+    }
+
+    #[test]
+    fn parses_unreads_notification() {
+        let config = toml::toml! {
+            [connection]
+            instance = "lemmy.ml"
+
+            [general]
+            accent_color = "LightGreen"
+
+            [unreads]
+            notify = "both"
+        };
+        let config = Config::table_to_config(config).unwrap();
+        assert_eq!(config.unreads.notify, UnreadsNotification::Both);
+        assert_eq!(config.unreads.poll_interval, 60);
+    }
+
+    #[test]
+    fn parses_feed() {
+        let config = toml::toml! {
+            [connection]
+            instance = "lemmy.ml"
+
+            [general]
+            accent_color = "LightGreen"
+
+            [feed]
+            default_tab = "all"
+            all_sort = "TopWeek"
+            saved_tab = true
+            hide_read = false
+        };
+        let config = Config::table_to_config(config).unwrap();
+        assert_eq!(config.feed.default_tab, Some(Tab::All));
+        assert!(config.feed.saved_tab);
+        assert_eq!(config.feed.hide_read, Some(false));
+        assert!(!config.feed.mark_read_on_scroll);
+        assert_eq!(config.feed.sort(Tab::All), Some("TopWeek"));
+        assert_eq!(config.feed.sort(Tab::Local), None);
+    }
+
+    #[test]
+    fn parses_nsfw_mode() {
+        let config = toml::toml! {
+            [connection]
+            instance = "lemmy.ml"
+
+            [general]
+            accent_color = "LightGreen"
+        };
+        let config = Config::table_to_config(config).unwrap();
+        assert_eq!(config.general.nsfw, NsfwMode::Blur);
+
+        let config = toml::toml! {
+            [connection]
+            instance = "lemmy.ml"
+
+            [general]
+            nsfw = "hide"
+        };
+        let config = Config::table_to_config(config).unwrap();
+        assert_eq!(config.general.nsfw, NsfwMode::Hide);
+    }
+
+    #[test]
+    fn parses_filters() {
+        let config = toml::toml! {
+            [connection]
+            instance = "lemmy.ml"
+
+            [general]
+            accent_color = "LightGreen"
+
+            [filters]
+            keywords = ["spoiler"]
+            regexes = ["(?i)^\\[meta\\]"]
+            communities = ["memes@lemmy.world"]
+        };
+        let config = Config::table_to_config(config).unwrap();
+        assert_eq!(config.filters.keywords, ["spoiler"]);
+        assert_eq!(config.filters.regexes, ["(?i)^\\[meta\\]"]);
+        assert!(config.filters.domains.is_empty());
+        assert!(config.filters.users.is_empty());
+        assert_eq!(config.filters.communities, ["memes@lemmy.world"]);
+    }
+
+    #[test]
+    fn accepts_local_instances() {
+        assert!(is_valid_instance("http://localhost:8536"));
+        assert!(is_valid_instance("127.0.0.1:8536"));
+        assert!(is_valid_instance("https://example.com/lemmy/"));
+        assert!(!is_valid_instance("lemmy.ml:port"));
+        assert!(!is_valid_instance(""));
+    }
 }
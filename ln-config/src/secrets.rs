@@ -0,0 +1,94 @@
+//! Helpers for files holding credentials, which must not be readable by other users.
+
+use std::{fs, io::Write, path::Path};
+
+use anyhow::{Context, Result};
+
+/// Reads a file holding a secret, refusing it if other users can read it.
+pub fn read_private_file(path: &Path) -> Result<String> {
+    check_permissions(path)?;
+    fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// Writes a secret to a file only its owner can access.
+pub fn write_private_file(path: &Path, contents: &str) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("failed to open {}", path.display()))?;
+
+    // `mode` only applies to newly created files.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+
+    file.write_all(contents.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn check_permissions(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path)
+        .with_context(|| format!("failed to read {}", path.display()))?
+        .permissions()
+        .mode();
+
+    if mode & 0o004 != 0 {
+        anyhow::bail!(
+            "{} is readable by everyone, refusing to use it (run `chmod 600` on it)",
+            path.display()
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_permissions(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    #[test]
+    fn refuses_world_readable_files() {
+        let path = std::env::temp_dir().join("lemmynator-world-readable-secret");
+        fs::write(&path, "hunter2").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        assert!(read_private_file(&path).is_err());
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn private_files_round_trip() {
+        let path = std::env::temp_dir().join("lemmynator-private-secret");
+        fs::write(&path, "old").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_private_file(&path, "hunter2").unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(read_private_file(&path).unwrap(), "hunter2");
+
+        fs::remove_file(path).unwrap();
+    }
+}
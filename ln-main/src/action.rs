@@ -1,25 +1,114 @@
 use crossterm::event::{KeyCode, KeyEvent};
-use lemmy_api_common::{
-    comment::GetCommentsResponse,
-    lemmy_db_schema::{ListingType, SortType},
-    person::GetUnreadCountResponse,
-    post::GetPostsResponse,
-};
+use std::fmt::Display;
+
+use lemmy_api_common::lemmy_db_schema::newtypes::{CommentId, CommunityId, PersonId, PostId};
 
-use crate::{tui::Event, types::LemmynatorPost};
+use crate::{
+    client::ClientError,
+    tui::Event,
+    types::{
+        Comment, CommunityDetails, CommunityInfo, CommunityRef, Feed, LemmynatorPost,
+        PersonDetails, PostsPage, SearchResults,
+    },
+};
 
 #[derive(Clone)]
 pub enum UpdateAction {
-    NewPage(ListingType, SortType, GetPostsResponse),
-    ViewPost(LemmynatorPost),
-    CommentsForCurrentPost(GetCommentsResponse),
-    UpdateUnreadsCount(GetUnreadCountResponse),
+    /// A page for the listing of the given generation, see [`crate::ui::listing::Listing`].
+    NewPage(Feed, u64, PostsPage),
+    ViewPost(Box<LemmynatorPost>),
+    ViewCommunity(CommunityRef),
+    ViewPerson(PersonId),
+    /// A page of saved comments for the Saved listing of the given generation.
+    SavedComments(u64, Vec<Comment>),
+    /// Comments of the given post, the cached ones first and then the fresh ones.
+    CommentsForPost(PostId, Vec<Comment>),
+    UpdateUnreadsCount(i64),
+    CommunityDetails(CommunityRef, Box<CommunityDetails>),
+    /// The community after (un)subscribing from it.
+    CommunityFollowed(Box<CommunityInfo>),
+    CommunityBlocked(Box<CommunityInfo>),
+    /// A page for the community browser of the given generation.
+    CommunitiesPage(u64, Vec<CommunityInfo>),
+    /// Results for the search of the given generation.
+    SearchResults(u64, Box<SearchResults>),
+    /// A person's details, or what they saved when asked for that only.
+    PersonDetails(PersonId, bool, Box<PersonDetails>),
+    Error(Operation, ClientError),
+    LoggedIn,
+    /// The instance's details arrived after the tabs were set up without them.
+    SiteLoaded,
+    CloseTotpPrompt,
+}
+
+/// A background operation, kept alongside its error so that it can be retried.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    Login,
+    FetchPage(Feed, u64),
+    FetchSavedComments(u64),
+    FetchComments(PostId),
+    FetchUnreadCount,
+    FetchSite,
+    FetchCommunity(CommunityRef),
+    FollowCommunity(CommunityId, bool),
+    BlockCommunity(CommunityId, bool),
+    FetchCommunities(u64),
+    Search(u64),
+    FetchPost(PostId),
+    FetchPerson(PersonId, bool),
+    BlockPerson(PersonId, bool),
+    SendMessage(PersonId, String),
+    Vote(PostId, i16),
+    SavePost(PostId, bool),
+    SaveComment(CommentId, bool),
+    MarkPostRead(PostId),
+    SwitchProfile(usize),
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operation::Login => write!(f, "Logging in"),
+            Operation::FetchPage(feed, _) => write!(f, "Loading {feed} posts"),
+            Operation::FetchSavedComments(_) => write!(f, "Loading saved comments"),
+            Operation::FetchComments(_) => write!(f, "Loading comments"),
+            Operation::FetchUnreadCount => write!(f, "Checking notifications"),
+            Operation::FetchSite => write!(f, "Loading the instance's details"),
+            Operation::FetchCommunity(_) => write!(f, "Loading the community"),
+            Operation::FollowCommunity(_, true) => write!(f, "Subscribing"),
+            Operation::FollowCommunity(_, false) => write!(f, "Unsubscribing"),
+            Operation::BlockCommunity(_, true) => write!(f, "Blocking the community"),
+            Operation::BlockCommunity(_, false) => write!(f, "Unblocking the community"),
+            Operation::FetchCommunities(_) => write!(f, "Loading communities"),
+            Operation::Search(_) => write!(f, "Searching"),
+            Operation::FetchPost(_) => write!(f, "Loading the post"),
+            Operation::FetchPerson(_, false) => write!(f, "Loading the profile"),
+            Operation::FetchPerson(_, true) => write!(f, "Loading saved posts and comments"),
+            Operation::BlockPerson(_, true) => write!(f, "Blocking the user"),
+            Operation::BlockPerson(_, false) => write!(f, "Unblocking the user"),
+            Operation::SendMessage(_, _) => write!(f, "Sending the message"),
+            Operation::Vote(_, _) => write!(f, "Voting"),
+            Operation::SavePost(_, true) => write!(f, "Saving the post"),
+            Operation::SavePost(_, false) => write!(f, "Unsaving the post"),
+            Operation::SaveComment(_, true) => write!(f, "Saving the comment"),
+            Operation::SaveComment(_, false) => write!(f, "Unsaving the comment"),
+            Operation::MarkPostRead(_) => write!(f, "Marking the post read"),
+            Operation::SwitchProfile(_) => write!(f, "Switching accounts"),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Action {
     Quit,
     ForceQuit,
+    /// Asks whether to log out.
+    Logout,
+    /// Logs out, forgets the token and quits.
+    ConfirmLogout,
+    ShowProfiles,
+    SwitchProfile(usize),
     Render,
     Up,
     Down,
@@ -27,12 +116,38 @@ pub enum Action {
     VoteDown,
     Confirm,
     ShowHelp,
+    Retry,
+    Dismiss,
     SwitchToInputMode,
     SwitchToNormalMode,
     ChangeFocus,
     ChangeSort,
     ChangeTab(u8),
     ChangeSubTab(u8),
+    /// Opens the community of the post in focus.
+    OpenCommunity,
+    /// Asks for the name of a community to open.
+    GoToCommunity,
+    ToggleFollow,
+    ToggleBlock,
+    BrowseCommunities,
+    Search,
+    /// Cycles between the listing types a view can be filtered by.
+    ChangeListingType,
+    /// Opens the profile of whoever wrote the post in focus.
+    OpenAuthor,
+    OpenOwnProfile,
+    SendMessage,
+    /// Saves or unsaves the post or comment in focus.
+    ToggleSave,
+    /// Shows or hides posts that were already read.
+    ToggleHideRead,
+    /// Shows or hides posts matching the filters.
+    ToggleFiltered,
+    /// Shows the thumbnail of the NSFW post in focus.
+    RevealNsfw,
+    /// Any other character, for popups that let entries be picked by key.
+    Char(char),
     Input(KeyEvent),
 }
 
@@ -67,15 +182,37 @@ fn keycode_to_action(key: KeyEvent) -> Option<Action> {
         KeyCode::Char('J') => Some(A::VoteDown),
         KeyCode::Char('K') => Some(A::VoteUp),
         KeyCode::Char('q') => Some(A::Quit),
+        KeyCode::Char('L') => Some(A::Logout),
+        KeyCode::Char('P') => Some(A::ShowProfiles),
         KeyCode::Char('?') => Some(A::ShowHelp),
-        KeyCode::Char(n @ '1'..='3') => {
+        KeyCode::Char('r') => Some(A::Retry),
+        KeyCode::Esc => Some(A::Dismiss),
+        KeyCode::Char(n @ ('1'..='3' | '5')) => {
             Some(A::ChangeTab(n.to_digit(10).expect("This is ok") as u8))
         }
         KeyCode::Char('!') => Some(A::ChangeSubTab(1)),
         KeyCode::Char('@') => Some(A::ChangeSubTab(2)),
         KeyCode::Char('#') => Some(A::ChangeSubTab(3)),
+        KeyCode::Char('$') => Some(A::ChangeSubTab(4)),
+        KeyCode::Char('%') => Some(A::ChangeSubTab(5)),
+        KeyCode::Char('^') => Some(A::ChangeSubTab(6)),
         KeyCode::Char('4') => Some(A::ChangeSort),
+        KeyCode::Char('C') => Some(A::OpenCommunity),
+        KeyCode::Char('g') => Some(A::GoToCommunity),
+        KeyCode::Char('f') => Some(A::ToggleFollow),
+        KeyCode::Char('B') => Some(A::ToggleBlock),
+        KeyCode::Char('b') => Some(A::BrowseCommunities),
+        KeyCode::Char('/') => Some(A::Search),
+        KeyCode::Char('l') => Some(A::ChangeListingType),
+        KeyCode::Char('u') => Some(A::OpenAuthor),
+        KeyCode::Char('U') => Some(A::OpenOwnProfile),
+        KeyCode::Char('p') => Some(A::SendMessage),
+        KeyCode::Char('v') => Some(A::ToggleSave),
+        KeyCode::Char('R') => Some(A::ToggleHideRead),
+        KeyCode::Char('F') => Some(A::ToggleFiltered),
+        KeyCode::Char('x') => Some(A::RevealNsfw),
         KeyCode::Enter => Some(Action::Confirm),
+        KeyCode::Char(c) => Some(A::Char(c)),
         _ => None,
     }
 }
@@ -1,8 +1,14 @@
 use std::{
-    fs::File,
-    io::{stdout, Read, Write},
+    fs,
+    io::stdout,
     panic::{set_hook, take_hook},
-    sync::{Arc, LazyLock, RwLock},
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, LazyLock, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard,
+    },
+    time::{Duration, Instant},
 };
 
 use crossterm::{
@@ -12,25 +18,30 @@ use crossterm::{
     terminal::{disable_raw_mode, LeaveAlternateScreen},
 };
 use lemmy_api_common::{
-    lemmy_db_schema::sensitive::SensitiveString,
-    person::{Login, LoginResponse},
+    lemmy_db_schema::{sensitive::SensitiveString, SortType},
+    person::Login,
 };
-use ln_config::{Config, CONFIG};
+use ln_config::{Config, Connection, CONFIG};
 use ratatui_image::picker::Picker;
-use reqwest::{
-    header::{HeaderMap, HeaderValue},
-    Client,
-};
 
 use crate::{
-    action::{event_to_action, Action, Mode, UpdateAction},
+    action::{event_to_action, Action, Mode, Operation, UpdateAction},
     app_key_event::AppKeyEvent,
+    cache::OfflineCache,
+    cli::Args,
+    client::{ClientError, LemmyClient, Recording},
+    filters::PostFilters,
     tui::{Event, Tui},
+    types::SiteInfo,
     ui::{components::Component, main_ui::MainWindow},
 };
 
-use anyhow::Result;
-use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use anyhow::{Context, Result};
+use tokio::sync::{
+    mpsc::{self, UnboundedReceiver, UnboundedSender},
+    Notify,
+};
+use tracing::{error, info};
 
 pub static PICKER: LazyLock<RwLock<Picker>> =
     LazyLock::new(|| RwLock::new(Picker::from_query_stdio().unwrap()));
@@ -41,90 +52,201 @@ pub struct App {
     update_rx: UnboundedReceiver<UpdateAction>,
     main_window: MainWindow,
     mode: Mode,
+    recording: Option<Recording>,
     ctx: Arc<Ctx>,
 }
 
 pub struct Ctx {
     pub action_tx: UnboundedSender<Action>,
     pub update_tx: UnboundedSender<UpdateAction>,
-    pub client: Client,
+    pub api: LemmyClient,
+    pub cache: OfflineCache,
+    pub profile: &'static Connection,
+    site: RwLock<SiteInfo>,
+    /// Overrides the account's choice of hiding read posts, once toggled.
+    hide_read: Mutex<Option<bool>>,
+    pub filters: PostFilters,
+    /// Lets posts matching the filters through anyway.
+    show_filtered: AtomicBool,
+    last_activity: Mutex<Instant>,
+    /// Woken up on every key press.
+    pub activity: Notify,
 }
 
 impl Ctx {
-    async fn new() -> Result<(
+    /// Connects to the instance of `profile`, logging in if it has an account.
+    ///
+    /// Every context gets its own channels, so tasks spawned for a replaced
+    /// context can't deliver anything into the new one.
+    async fn new(
+        profile: &'static Connection,
+        recording: Option<&Recording>,
+    ) -> Result<(
         Self,
         UnboundedReceiver<Action>,
         UnboundedReceiver<UpdateAction>,
     )> {
+        let filters = PostFilters::new(&CONFIG.filters)?;
         let (action_tx, action_rx) = mpsc::unbounded_channel();
         let (update_tx, update_rx) = mpsc::unbounded_channel();
 
-        let user_agent = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
-        let client = Client::builder().user_agent(user_agent).build()?;
+        let api = LemmyClient::new(profile.base_url(), &CONFIG.network, recording.cloned())?;
+        api.negotiate_version()
+            .await
+            .with_context(|| format!("can't use {}", profile.instance))?;
+
+        if let Some(username) = &profile.username {
+            let login_req = Login {
+                username_or_email: SensitiveString::from(username.clone()),
+                password: SensitiveString::from(profile.password()?),
+                ..Default::default()
+            };
+
+            // Recorded sessions always log in, so that the login is part of them.
+            let jwt_cache = match recording {
+                Some(_) => None,
+                None => Some(jwt_cache_path(profile)?),
+            };
+            api.authenticate(login_req, jwt_cache).await?;
+        }
 
-        let login_req = Login {
-            username_or_email: SensitiveString::from(CONFIG.connection.username.clone()),
-            password: SensitiveString::from(CONFIG.connection.password.clone()),
-            ..Default::default()
+        let ctx = Ctx {
+            action_tx,
+            update_tx,
+            api,
+            // Cached posts would make replays depend on whatever was browsed last.
+            cache: match recording {
+                Some(_) => OfflineCache::disabled(),
+                None => OfflineCache::new(profile)?,
+            },
+            profile,
+            site: RwLock::new(SiteInfo::fallback(profile)),
+            hide_read: Mutex::new(CONFIG.feed.hide_read),
+            filters,
+            show_filtered: AtomicBool::new(false),
+            last_activity: Mutex::new(Instant::now()),
+            activity: Notify::new(),
         };
 
-        let xdg_dirs = Config::get_xdg_dirs();
-        let jwt_file = xdg_dirs.get_cache_file("jwt");
-        let jwt = if jwt_file.exists() {
-            let mut buf = String::new();
-            File::open(jwt_file).unwrap().read_to_string(&mut buf)?;
-            buf
-        } else {
-            let res: LoginResponse = client
-                .post(format!(
-                    "https://{}/api/v3/user/login",
-                    CONFIG.connection.instance
-                ))
-                .json(&login_req)
-                .send()
-                .await?
-                .json()
-                .await?;
-            let jwt = res.jwt.unwrap().to_string();
-            File::create(xdg_dirs.place_cache_file("jwt").unwrap())
-                .unwrap()
-                .write(jwt.as_bytes())?;
-            jwt
-        };
+        if !ctx.api.needs_totp() {
+            ctx.load_site().await;
+        }
 
-        let mut header_map = HeaderMap::new();
-        header_map.insert(
-            reqwest::header::AUTHORIZATION,
-            HeaderValue::from_str(&format!("Bearer {}", jwt))?,
-        );
-        let client = Client::builder()
-            .user_agent(user_agent)
-            .default_headers(header_map)
-            .build()?;
-
-        Ok((
-            Ctx {
-                action_tx,
-                update_tx,
-                client,
-            },
-            action_rx,
-            update_rx,
-        ))
+        Ok((ctx, action_rx, update_rx))
+    }
+
+    /// Refreshes what we know about the instance and the account, returning
+    /// whether that worked.
+    pub async fn load_site(&self) -> bool {
+        match self.api.get_site().await {
+            Ok(res) => {
+                *self.site.write().unwrap() = res;
+                true
+            }
+            Err(e) => {
+                self.send_update_action(UpdateAction::Error(Operation::FetchSite, e));
+                false
+            }
+        }
+    }
+
+    pub fn site(&self) -> RwLockReadGuard<'_, SiteInfo> {
+        self.site.read().unwrap()
+    }
+
+    pub fn site_mut(&self) -> RwLockWriteGuard<'_, SiteInfo> {
+        self.site.write().unwrap()
+    }
+
+    /// Whether read posts are left out of listings.
+    pub fn hide_read(&self) -> bool {
+        let hide_read = *self.hide_read.lock().unwrap();
+        hide_read.unwrap_or_else(|| !self.site().show_read_posts)
+    }
+
+    pub fn toggle_hide_read(&self) {
+        let hide_read = !self.hide_read();
+        *self.hide_read.lock().unwrap() = Some(hide_read);
+    }
+
+    pub fn show_filtered(&self) -> bool {
+        self.show_filtered.load(Ordering::Relaxed)
+    }
+
+    pub fn toggle_show_filtered(&self) {
+        self.show_filtered.fetch_xor(true, Ordering::Relaxed);
+    }
+
+    pub fn mark_active(&self) {
+        *self.last_activity.lock().unwrap() = Instant::now();
+        self.activity.notify_waiters();
     }
 
+    /// How long it's been since the user last pressed a key.
+    pub fn idle_for(&self) -> Duration {
+        self.last_activity.lock().unwrap().elapsed()
+    }
+
+    /// Whether we are browsing without an account, in which case nothing can be changed.
+    pub fn is_anonymous(&self) -> bool {
+        !self.api.is_authenticated()
+    }
+
+    // The receiving side only goes away once this context has been replaced,
+    // at which point whatever is still being sent is stale anyway.
     pub fn send_action(&self, action: Action) {
-        self.action_tx.send(action).unwrap();
+        let _ = self.action_tx.send(action);
     }
 
     pub fn send_update_action(&self, action: UpdateAction) {
-        self.update_tx.send(action).unwrap();
+        let _ = self.update_tx.send(action);
+    }
+}
+
+/// Where the token of `profile` is kept.
+///
+/// Before profiles, the one token was kept in a `jwt` file. It belonged to
+/// `[connection]`, so that's where it moves.
+fn jwt_cache_path(profile: &Connection) -> Result<PathBuf> {
+    let xdg_dirs = Config::get_xdg_dirs();
+    let path = xdg_dirs.place_cache_file(profile.jwt_cache_name())?;
+
+    if let Some(legacy) = xdg_dirs
+        .find_cache_file("jwt")
+        .filter(|path| path.is_file())
+    {
+        if std::ptr::eq(profile, &CONFIG.connection) {
+            fs::rename(&legacy, &path)
+                .with_context(|| format!("failed to move {}", legacy.display()))?;
+        }
     }
+
+    Ok(path)
 }
 
 impl App {
-    pub async fn new() -> Result<Self> {
-        let (ctx, action_rx, update_rx) = Ctx::new().await?;
+    pub async fn new(args: Args) -> Result<Self> {
+        let profile = match &args.profile {
+            Some(name) => CONFIG
+                .profile(name)
+                .with_context(|| format!("there's no profile called \"{name}\""))?,
+            None => &CONFIG.connection,
+        };
+
+        // Typos would otherwise silently fall back to the default sort.
+        for sort in [
+            &CONFIG.feed.subscribed_sort,
+            &CONFIG.feed.local_sort,
+            &CONFIG.feed.all_sort,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            SortType::from_str(sort)
+                .with_context(|| format!("\"{sort}\" in [feed] is not a sort Lemmy knows"))?;
+        }
+
+        let (ctx, action_rx, update_rx) = Ctx::new(profile, args.recording.as_ref()).await?;
         let ctx = Arc::new(ctx);
 
         Ok(Self {
@@ -133,6 +255,7 @@ impl App {
             action_rx,
             mode: Mode::Normal,
             update_rx,
+            recording: args.recording,
             ctx,
         })
     }
@@ -167,6 +290,7 @@ impl App {
                     let event = event.unwrap();
                     match event {
                         Event::Key(key_event) => {
+                            self.ctx.mark_active();
                             let app_key_event = AppKeyEvent::from(key_event);
                             if app_key_event.is_ctrl_c() {
                                 self.should_quit = true;
@@ -189,6 +313,9 @@ impl App {
                     if let Some(action) = action {
                         if action.is_render() {
                             self.render(tui)?;
+                        } else if let Action::SwitchProfile(idx) = action {
+                            self.switch_profile(idx).await;
+                            self.render(tui)?;
                         } else {
                             self.handle_action(action);
                         }
@@ -208,6 +335,43 @@ impl App {
         }
     }
 
+    /// Rebuilds the context and every view for another profile.
+    /// Starts over with another profile. If that fails, the current one is
+    /// kept and the error is shown in it.
+    async fn switch_profile(&mut self, idx: usize) {
+        let Some(profile) = CONFIG.profiles().nth(idx) else {
+            return;
+        };
+
+        let switched = async {
+            let (ctx, action_rx, update_rx) = Ctx::new(profile, self.recording.as_ref()).await?;
+            let ctx = Arc::new(ctx);
+            let main_window = MainWindow::new(Arc::clone(&ctx)).await?;
+            anyhow::Ok((ctx, main_window, action_rx, update_rx))
+        };
+
+        match switched.await {
+            Ok((ctx, main_window, action_rx, update_rx)) => {
+                self.main_window = main_window;
+                self.action_rx = action_rx;
+                self.update_rx = update_rx;
+                self.mode = Mode::Normal;
+                self.ctx = ctx;
+                info!("Switched to profile {}", profile.name());
+            }
+            Err(e) => {
+                let error = match e.downcast::<ClientError>() {
+                    Ok(e) => e,
+                    Err(e) => ClientError::Other(format!("{e:#}")),
+                };
+                self.handle_update_action(UpdateAction::Error(
+                    Operation::SwitchProfile(idx),
+                    error,
+                ));
+            }
+        }
+    }
+
     fn render(&mut self, tui: &mut Tui) -> Result<()> {
         tui.terminal.draw(|f| {
             self.main_window.render(f, f.area());
@@ -226,6 +390,16 @@ impl App {
                 self.ctx.send_action(Action::Render);
             }
 
+            Action::ConfirmLogout if !self.ctx.is_anonymous() => {
+                let ctx = Arc::clone(&self.ctx);
+                tokio::task::spawn(async move {
+                    if let Err(e) = ctx.api.logout().await {
+                        error!("Logout did not fully succeed: {e:?}");
+                    }
+                    ctx.send_action(Action::ForceQuit);
+                });
+            }
+
             Action::SwitchToNormalMode => {
                 self.mode = Mode::Normal;
                 self.ctx.send_action(Action::Render);
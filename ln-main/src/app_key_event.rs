@@ -17,18 +17,4 @@ impl AppKeyEvent {
         }
         false
     }
-
-    fn keybinding(&self) -> (KeyCode, KeyModifiers) {
-        match self.0.code {
-            KeyCode::Char(e) => {
-                let modifier = if e.is_uppercase() {
-                    KeyModifiers::NONE
-                } else {
-                    self.0.modifiers
-                };
-                (self.0.code, modifier)
-            }
-            _ => (self.0.code, self.0.modifiers),
-        }
-    }
 }
@@ -0,0 +1,206 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::Result;
+use lemmy_api_common::lemmy_db_schema::{newtypes::PostId, SortType};
+use ln_config::{secrets, Config, Connection};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::warn;
+
+use crate::types::{Comment, Feed, PostsPage};
+
+/// How many posts of a single listing are kept around.
+const MAX_CACHED_POSTS: usize = 100;
+/// Comments of posts and posts of communities which weren't opened for this
+/// long are thrown away.
+const MAX_AGE: Duration = Duration::from_secs(60 * 60 * 24 * 14);
+
+/// Keeps the last fetched posts and comments on disk, so there's something to
+/// show before the instance answers, or when it can't be reached at all.
+///
+/// Failing to read or write the cache is never fatal, it only gets logged.
+pub struct OfflineCache {
+    dir: Option<PathBuf>,
+}
+
+impl OfflineCache {
+    pub fn new(profile: &Connection) -> Result<Self> {
+        let dir = Config::get_xdg_dirs()
+            .create_cache_directory(format!("offline/{}", profile.cache_key()))?;
+        fs::create_dir_all(dir.join("comments"))?;
+        fs::create_dir_all(dir.join("communities"))?;
+
+        let cache = Self { dir: Some(dir) };
+        cache.prune("comments");
+        cache.prune("communities");
+        Ok(cache)
+    }
+
+    /// A cache which never has anything and forgets whatever it's given.
+    pub fn disabled() -> Self {
+        Self { dir: None }
+    }
+
+    fn listing_path(&self, feed: Feed, sort_type: SortType) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        let path = match feed {
+            Feed::Listing(listing_type) => {
+                dir.join(format!("posts-{listing_type:?}-{sort_type:?}.json"))
+            }
+            Feed::Community(community_id) => dir
+                .join("communities")
+                .join(format!("{}-{sort_type:?}.json", community_id.0)),
+            Feed::Saved => dir.join(format!("posts-Saved-{sort_type:?}.json")),
+        };
+        Some(path)
+    }
+
+    fn comments_path(&self, post_id: PostId) -> Option<PathBuf> {
+        let dir = self.dir.as_ref()?;
+        Some(dir.join("comments").join(format!("{}.json", post_id.0)))
+    }
+
+    pub fn load_posts(&self, feed: Feed, sort_type: SortType) -> Option<PostsPage> {
+        read(&self.listing_path(feed, sort_type)?)
+    }
+
+    /// Stores a freshly fetched page. The first page of a listing replaces
+    /// whatever was cached before, later ones are appended to it.
+    pub fn store_posts(
+        &self,
+        feed: Feed,
+        sort_type: SortType,
+        is_first_page: bool,
+        page: &PostsPage,
+    ) {
+        let Some(path) = self.listing_path(feed, sort_type) else {
+            return;
+        };
+
+        let mut cached = match read::<PostsPage>(&path) {
+            Some(cached) if !is_first_page => cached,
+            _ => PostsPage::default(),
+        };
+
+        if cached.posts.len() >= MAX_CACHED_POSTS {
+            return;
+        }
+
+        cached.posts.extend(page.posts.iter().cloned());
+        cached.posts.truncate(MAX_CACHED_POSTS);
+        cached.next_page.clone_from(&page.next_page);
+        write(&path, &cached);
+    }
+
+    pub fn load_comments(&self, post_id: PostId) -> Option<Vec<Comment>> {
+        read(&self.comments_path(post_id)?)
+    }
+
+    pub fn store_comments(&self, post_id: PostId, comments: &[Comment]) {
+        if let Some(path) = self.comments_path(post_id) {
+            write(&path, comments);
+        }
+    }
+
+    fn prune(&self, subdir: &str) {
+        let Some(Ok(entries)) = self.dir.as_ref().map(|dir| fs::read_dir(dir.join(subdir))) else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let is_old = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .is_some_and(|age| age > MAX_AGE);
+
+            if is_old {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+fn read<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let bytes = fs::read(path).ok()?;
+    match serde_json::from_slice(&bytes) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("Ignoring unreadable cache file {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Cached posts and comments can tell what the account sees, so only its owner may read them.
+fn write<T: Serialize + ?Sized>(path: &Path, value: &T) {
+    let result = serde_json::to_string(value)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| secrets::write_private_file(path, &json));
+
+    if let Err(e) = result {
+        warn!("Could not write cache file {}: {e}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lemmy_api_common::lemmy_db_schema::{newtypes::CommunityId, ListingType};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// A cache in a directory of its own, removed along with the guard.
+    fn cache() -> (TempDir, OfflineCache) {
+        let guard = tempfile::tempdir().unwrap();
+        let dir = guard.path().to_path_buf();
+        fs::create_dir_all(dir.join("comments")).unwrap();
+        fs::create_dir_all(dir.join("communities")).unwrap();
+        (guard, OfflineCache { dir: Some(dir) })
+    }
+
+    #[test]
+    fn listings_have_their_own_files() {
+        let (_guard, cache) = cache();
+        let path = |feed, sort_type| cache.listing_path(feed, sort_type).unwrap();
+
+        let local_hot = path(Feed::Listing(ListingType::Local), SortType::Hot);
+        let local_new = path(Feed::Listing(ListingType::Local), SortType::New);
+        let saved_hot = path(Feed::Saved, SortType::Hot);
+        let community_hot = path(Feed::Community(CommunityId(7)), SortType::Hot);
+
+        assert!(local_hot.ends_with("posts-Local-Hot.json"));
+        assert!(local_new.ends_with("posts-Local-New.json"));
+        assert!(saved_hot.ends_with("posts-Saved-Hot.json"));
+        assert!(community_hot.ends_with("communities/7-Hot.json"));
+        assert!(cache
+            .comments_path(PostId(7))
+            .unwrap()
+            .ends_with("comments/7.json"));
+    }
+
+    #[test]
+    fn stores_and_loads_comments_per_post() {
+        let (_guard, cache) = cache();
+        let comments: Vec<Comment> = vec![];
+
+        assert!(cache.load_comments(PostId(1)).is_none());
+        cache.store_comments(PostId(1), &comments);
+        assert!(cache.load_comments(PostId(1)).is_some());
+        assert!(cache.load_comments(PostId(2)).is_none());
+    }
+
+    #[test]
+    fn disabled_cache_forgets_everything() {
+        let cache = OfflineCache::disabled();
+        cache.store_comments(PostId(1), &[]);
+        assert!(cache.load_comments(PostId(1)).is_none());
+        assert!(cache
+            .load_posts(Feed::Listing(ListingType::All), SortType::Hot)
+            .is_none());
+    }
+}
@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+
+use crate::client::Recording;
+
+const USAGE: &str = "\
+Usage: lemmynator [OPTIONS]
+
+Options:
+  --profile <NAME>  Start with the given profile from config.toml
+  --record <DIR>    Save every response from the instance to DIR
+  --replay <DIR>    Serve responses recorded with --record instead of going online
+  -h, --help        Print this help
+";
+
+#[derive(Default)]
+pub struct Args {
+    pub profile: Option<String>,
+    pub recording: Option<Recording>,
+}
+
+impl Args {
+    pub fn parse() -> Result<Self> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--profile" => {
+                    parsed.profile = Some(args.next().context("--profile needs a name")?);
+                }
+                "--record" | "--replay" => {
+                    if parsed.recording.is_some() {
+                        bail!("only one of --record and --replay can be given");
+                    }
+                    let dir = PathBuf::from(
+                        args.next()
+                            .with_context(|| format!("{arg} needs a directory"))?,
+                    );
+                    parsed.recording = Some(if arg == "--record" {
+                        Recording::Record(dir)
+                    } else {
+                        Recording::Replay(dir)
+                    });
+                }
+                "-h" | "--help" => {
+                    print!("{USAGE}");
+                    std::process::exit(0);
+                }
+                _ => bail!("unexpected argument {arg}\n\n{USAGE}"),
+            }
+        }
+
+        Ok(parsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args> {
+        Args::parse_from(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn parses_nothing() {
+        let args = parse(&[]).unwrap();
+        assert!(args.profile.is_none());
+        assert!(args.recording.is_none());
+    }
+
+    #[test]
+    fn parses_profile() {
+        let args = parse(&["--profile", "work"]).unwrap();
+        assert_eq!(args.profile.as_deref(), Some("work"));
+    }
+
+    #[test]
+    fn rejects_profile_without_name() {
+        assert!(parse(&["--profile"]).is_err());
+    }
+
+    #[test]
+    fn rejects_unknown_arguments() {
+        assert!(parse(&["--verbose"]).is_err());
+    }
+
+    #[test]
+    fn parses_recording() {
+        let args = parse(&["--record", "out"]).unwrap();
+        assert!(matches!(args.recording, Some(Recording::Record(dir)) if dir.as_os_str() == "out"));
+
+        let args = parse(&["--replay", "out"]).unwrap();
+        assert!(matches!(args.recording, Some(Recording::Replay(dir)) if dir.as_os_str() == "out"));
+    }
+
+    #[test]
+    fn rejects_recording_and_replaying_together() {
+        assert!(parse(&["--record", "a", "--replay", "b"]).is_err());
+        assert!(parse(&["--replay"]).is_err());
+    }
+}
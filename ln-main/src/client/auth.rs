@@ -0,0 +1,139 @@
+use std::{fs, path::PathBuf};
+
+use anyhow::{Context, Result};
+use lemmy_api_common::{person::Login, LemmyErrorType};
+use ln_config::secrets;
+use tracing::{info, warn};
+
+use super::{ClientError, ClientResult, LemmyClient};
+
+/// What the client needs to log in again on its own once the token expires.
+pub(super) struct Session {
+    login: Login,
+    /// Where the token is kept between runs, if anywhere.
+    jwt_cache: Option<PathBuf>,
+}
+
+impl LemmyClient {
+    /// Authenticates with the cached token if there is one, logging in otherwise.
+    ///
+    /// Accounts with 2FA enabled are left waiting for a code, see [`Self::needs_totp`].
+    pub async fn authenticate(&self, login: Login, jwt_cache: Option<PathBuf>) -> Result<()> {
+        let cached_jwt = match &jwt_cache {
+            Some(jwt_cache) if jwt_cache.exists() => match secrets::read_private_file(jwt_cache) {
+                Ok(jwt) => Some(jwt),
+                Err(e) => {
+                    warn!("Ignoring the cached token: {e:?}");
+                    None
+                }
+            },
+            _ => None,
+        };
+
+        *self.session.write().unwrap() = Some(Session { login, jwt_cache });
+
+        match cached_jwt {
+            Some(jwt) => self.set_jwt(Some(jwt)),
+            None => match self.login_and_cache().await {
+                Err(e) if e.is_missing_totp() => info!("Login is waiting for a 2FA code"),
+                res => res?,
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Credentials are known, but logging in is waiting for a 2FA code.
+    pub fn needs_totp(&self) -> bool {
+        self.is_authenticated() && self.jwt().is_none()
+    }
+
+    /// Finishes logging in with a 2FA code.
+    pub async fn submit_totp(&self, totp_2fa_token: String) -> ClientResult<()> {
+        self.set_totp(Some(totp_2fa_token));
+        let res = self.login_and_cache().await;
+        // Codes are single use, so a later re-login has to ask for a new one.
+        self.set_totp(None);
+        res
+    }
+
+    fn set_totp(&self, totp_2fa_token: Option<String>) {
+        if let Some(session) = &mut *self.session.write().unwrap() {
+            session.login.totp_2fa_token = totp_2fa_token;
+        }
+    }
+
+    /// Invalidates the token on the instance and forgets it locally.
+    pub async fn logout(&self) -> Result<()> {
+        let res = self.invalidate_jwt().await;
+
+        self.set_jwt(None);
+        if let Some(jwt_cache) = self
+            .session
+            .write()
+            .unwrap()
+            .take()
+            .and_then(|session| session.jwt_cache)
+        {
+            if jwt_cache.exists() {
+                fs::remove_file(&jwt_cache)
+                    .with_context(|| format!("failed to remove {}", jwt_cache.display()))?;
+            }
+        }
+
+        res.context("failed to invalidate the token on the instance")?;
+        Ok(())
+    }
+
+    pub fn is_authenticated(&self) -> bool {
+        self.session.read().unwrap().is_some()
+    }
+
+    /// Logs in again, unless another request already did so since `stale_jwt` was used.
+    pub(super) async fn relogin(&self, stale_jwt: Option<&str>) -> ClientResult<()> {
+        let _guard = self.relogin_lock.lock().await;
+
+        if self.jwt().as_deref() != stale_jwt {
+            return Ok(());
+        }
+
+        self.login_and_cache().await
+    }
+
+    async fn login_and_cache(&self) -> ClientResult<()> {
+        let login = self
+            .session
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|session| session.login.clone())
+            .ok_or(ClientError::Api(LemmyErrorType::NotLoggedIn))?;
+
+        let res = self.login(&login).await?;
+        let jwt = res
+            .jwt
+            .ok_or_else(|| ClientError::Decode("login response did not contain a token".into()))?
+            .to_string();
+
+        self.set_jwt(Some(jwt.clone()));
+        info!("Logged in as {}", &*login.username_or_email);
+
+        if let Err(e) = self.write_jwt_cache(&jwt) {
+            warn!("Could not cache the token: {e:?}");
+        }
+
+        Ok(())
+    }
+
+    fn write_jwt_cache(&self, jwt: &str) -> Result<()> {
+        let session = self.session.read().unwrap();
+        let Some(jwt_cache) = session
+            .as_ref()
+            .and_then(|session| session.jwt_cache.as_ref())
+        else {
+            return Ok(());
+        };
+
+        secrets::write_private_file(jwt_cache, jwt)
+    }
+}
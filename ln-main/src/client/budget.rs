@@ -0,0 +1,321 @@
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    sync::Mutex,
+    time::Duration,
+};
+
+use tokio::{
+    sync::{Semaphore, SemaphorePermit},
+    time::Instant,
+};
+use tracing::warn;
+
+use super::{ClientError, ClientResult};
+
+const BASE_DELAY: Duration = Duration::from_millis(500);
+/// Longest we are willing to wait before retrying. If the instance asks for
+/// more than this, the error is reported instead.
+const MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// Limits how many requests of one kind run at once and how quickly they are
+/// started, and retries the ones that failed for transient reasons.
+///
+/// Requests share the concurrency, but are paced per endpoint: instances limit
+/// searches apart from everything else, and every image host has limits of its
+/// own. Being rate limited on one endpoint doesn't hold back the others.
+pub(super) struct RequestBudget {
+    permits: Semaphore,
+    min_interval: Duration,
+    /// When the next request to each endpoint may start.
+    next_slots: Mutex<HashMap<String, Instant>>,
+    max_retries: u32,
+}
+
+impl RequestBudget {
+    pub fn new(concurrency: usize, requests_per_second: f64, max_retries: u32) -> Self {
+        let min_interval = if requests_per_second > 0.0 {
+            Duration::from_secs_f64(1.0 / requests_per_second)
+        } else {
+            Duration::ZERO
+        };
+
+        Self {
+            permits: Semaphore::new(concurrency.max(1)),
+            min_interval,
+            next_slots: Mutex::new(HashMap::new()),
+            max_retries,
+        }
+    }
+
+    /// Waits for a free slot, both in terms of rate and concurrency.
+    ///
+    /// The pace is waited out first, so that a request held back on one
+    /// endpoint doesn't keep a permit the others could use meanwhile.
+    async fn acquire(&self, endpoint: &str) -> SemaphorePermit<'_> {
+        let slot = {
+            let mut next_slots = self.next_slots.lock().unwrap();
+            let now = Instant::now();
+            let next_slot = next_slots.entry(endpoint.to_string()).or_insert(now);
+            // Time left unused isn't saved up for a burst later.
+            let slot = (*next_slot).max(now);
+            *next_slot = slot + self.min_interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+
+        self.permits
+            .acquire()
+            .await
+            .expect("budget semaphore is never closed")
+    }
+
+    /// Holds back every request to `endpoint` for `delay`.
+    fn pause(&self, endpoint: &str, delay: Duration) {
+        let mut next_slots = self.next_slots.lock().unwrap();
+        let resume = Instant::now() + delay;
+        let next_slot = next_slots.entry(endpoint.to_string()).or_insert(resume);
+        *next_slot = (*next_slot).max(resume);
+    }
+
+    /// Runs `request` within the budget, retrying it with exponential backoff.
+    ///
+    /// Requests which are not `idempotent` are only retried when the instance
+    /// rate limited them, as then they were certainly not processed.
+    pub async fn run<T, F, Fut>(
+        &self,
+        endpoint: &str,
+        idempotent: bool,
+        mut request: F,
+    ) -> ClientResult<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = ClientResult<T>>,
+    {
+        let mut attempt = 0;
+
+        loop {
+            let error = {
+                let _permit = self.acquire(endpoint).await;
+                match request().await {
+                    Ok(res) => return Ok(res),
+                    Err(e) => e,
+                }
+            };
+
+            let retryable = match &error {
+                ClientError::RateLimited { .. } => true,
+                e => idempotent && e.is_transient(),
+            };
+            if !retryable || attempt >= self.max_retries {
+                return Err(error);
+            }
+
+            if let ClientError::RateLimited { retry_after } = error {
+                let delay = retry_after.unwrap_or_else(|| backoff(attempt));
+                if delay > MAX_DELAY {
+                    return Err(error);
+                }
+                // Everything else sent there would be rejected as well, so hold it all back.
+                warn!("Rate limited on {endpoint}, pausing it for {delay:?}");
+                self.pause(endpoint, delay);
+            } else {
+                let delay = backoff(attempt);
+                warn!("Request failed ({error}), retrying in {delay:?}");
+                tokio::time::sleep(delay).await;
+            }
+
+            attempt += 1;
+        }
+    }
+}
+
+/// Exponential backoff with jitter, so that parallel requests do not all come back at once.
+fn backoff(attempt: u32) -> Duration {
+    let delay = BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_DELAY);
+    // Every `RandomState` is seeded differently, which is all the randomness we need.
+    let random = RandomState::new().build_hasher().finish();
+    let jitter = (random % 1000) as f64 / 1000.0;
+    delay.mul_f64(0.5 + jitter / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    /// Succeeds once it has failed `failures` times with `error`.
+    async fn flaky(calls: &AtomicU32, failures: u32, error: ClientError) -> ClientResult<u32> {
+        let call = calls.fetch_add(1, Ordering::SeqCst);
+        if call < failures {
+            Err(error)
+        } else {
+            Ok(call)
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn paces_requests_to_one_endpoint() {
+        let budget = RequestBudget::new(4, 2.0, 0);
+        let start = Instant::now();
+
+        for expected in [0, 500, 1000] {
+            budget
+                .run("message", true, || async { Ok(()) })
+                .await
+                .unwrap();
+            assert_eq!(start.elapsed(), Duration::from_millis(expected));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn paces_endpoints_apart() {
+        let budget = RequestBudget::new(4, 1.0, 0);
+        let start = Instant::now();
+
+        budget
+            .run("message", true, || async { Ok(()) })
+            .await
+            .unwrap();
+        budget
+            .run("search", true, || async { Ok(()) })
+            .await
+            .unwrap();
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn paced_requests_leave_permits_to_others() {
+        let budget = RequestBudget::new(1, 1.0, 0);
+        budget
+            .run("message", true, || async { Ok(()) })
+            .await
+            .unwrap();
+        let start = Instant::now();
+
+        let (paced, other) = tokio::join!(
+            budget.run("message", true, || async { Ok(start.elapsed()) }),
+            budget.run("search", true, || async { Ok(start.elapsed()) }),
+        );
+        assert_eq!(paced.unwrap(), Duration::from_secs(1));
+        assert_eq!(other.unwrap(), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_save_up_idle_time() {
+        let budget = RequestBudget::new(4, 1.0, 0);
+        budget
+            .run("message", true, || async { Ok(()) })
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_secs(10)).await;
+
+        let start = Instant::now();
+        budget
+            .run("message", true, || async { Ok(()) })
+            .await
+            .unwrap();
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        budget
+            .run("message", true, || async { Ok(()) })
+            .await
+            .unwrap();
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn waits_as_long_as_the_instance_asks() {
+        let budget = RequestBudget::new(4, 0.0, 3);
+        let calls = AtomicU32::new(0);
+        let rate_limited = ClientError::RateLimited {
+            retry_after: Some(Duration::from_secs(5)),
+        };
+        let start = Instant::now();
+
+        let res = budget
+            .run("message", false, || flaky(&calls, 1, rate_limited.clone()))
+            .await;
+        assert_eq!(res.unwrap(), 1);
+        assert_eq!(start.elapsed(), Duration::from_secs(5));
+
+        // Only the endpoint that was rate limited got paused.
+        let start = Instant::now();
+        budget
+            .run("search", true, || async { Ok(()) })
+            .await
+            .unwrap();
+        assert_eq!(start.elapsed(), Duration::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_on_long_rate_limits() {
+        let budget = RequestBudget::new(4, 0.0, 3);
+        let calls = AtomicU32::new(0);
+        let rate_limited = ClientError::RateLimited {
+            retry_after: Some(MAX_DELAY + Duration::from_secs(1)),
+        };
+
+        let res = budget
+            .run("message", true, || flaky(&calls, 1, rate_limited.clone()))
+            .await;
+        assert!(matches!(res, Err(ClientError::RateLimited { .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_transient_failures_with_backoff() {
+        let budget = RequestBudget::new(4, 0.0, 3);
+        let calls = AtomicU32::new(0);
+        let start = Instant::now();
+
+        let res = budget
+            .run("message", true, || flaky(&calls, 2, ClientError::Timeout))
+            .await;
+        assert_eq!(res.unwrap(), 2);
+        // Half to all of 500ms, then of 1s.
+        assert!(start.elapsed() >= Duration::from_millis(750));
+        assert!(start.elapsed() <= Duration::from_millis(1500));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stops_after_max_retries() {
+        let budget = RequestBudget::new(4, 0.0, 2);
+        let calls = AtomicU32::new(0);
+
+        let res = budget
+            .run("message", true, || flaky(&calls, 10, ClientError::Timeout))
+            .await;
+        assert!(matches!(res, Err(ClientError::Timeout)));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_resend_what_may_have_been_processed() {
+        let budget = RequestBudget::new(4, 0.0, 3);
+        let calls = AtomicU32::new(0);
+
+        let res = budget
+            .run("message", false, || flaky(&calls, 1, ClientError::Timeout))
+            .await;
+        assert!(matches!(res, Err(ClientError::Timeout)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn backoff_doubles_with_jitter_up_to_the_cap() {
+        for attempt in 0..4 {
+            let full = BASE_DELAY * 2u32.pow(attempt);
+            let delay = backoff(attempt);
+            assert!(
+                delay >= full / 2 && delay <= full,
+                "{delay:?} for {attempt}"
+            );
+        }
+        assert!(backoff(20) <= MAX_DELAY);
+        assert!(backoff(20) >= MAX_DELAY / 2);
+    }
+}
@@ -0,0 +1,120 @@
+use std::{fmt::Display, time::Duration};
+
+use lemmy_api_common::LemmyErrorType;
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    StatusCode,
+};
+
+pub type ClientResult<T> = std::result::Result<T, ClientError>;
+
+#[derive(Debug, Clone)]
+pub enum ClientError {
+    /// The request did not complete in time.
+    Timeout,
+    /// The instance could not be reached at all.
+    Network(String),
+    /// The instance is refusing requests because we sent too many of them.
+    RateLimited { retry_after: Option<Duration> },
+    /// The instance answered with one of Lemmy's own errors.
+    Api(LemmyErrorType),
+    /// The instance answered with an error Lemmynator does not know about.
+    Http { status: StatusCode, body: String },
+    /// The response body did not have the expected shape.
+    Decode(String),
+    /// Anything that went wrong before a request could be made, like reading credentials.
+    Other(String),
+}
+
+impl ClientError {
+    /// Whether the instance refused the request because our token is invalid or expired.
+    pub fn is_auth_failure(&self) -> bool {
+        match self {
+            ClientError::Api(e) => matches!(
+                e,
+                LemmyErrorType::NotLoggedIn | LemmyErrorType::IncorrectLogin
+            ),
+            ClientError::Http { status, .. } => *status == StatusCode::UNAUTHORIZED,
+            _ => false,
+        }
+    }
+
+    /// Whether logging in failed only because the account needs a 2FA code.
+    pub fn is_missing_totp(&self) -> bool {
+        matches!(self, ClientError::Api(LemmyErrorType::MissingTotpToken))
+    }
+
+    /// Whether the same request might succeed if it's simply sent again later.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ClientError::Timeout | ClientError::Network(_) | ClientError::RateLimited { .. } => {
+                true
+            }
+            ClientError::Http { status, .. } => matches!(
+                *status,
+                StatusCode::BAD_GATEWAY
+                    | StatusCode::SERVICE_UNAVAILABLE
+                    | StatusCode::GATEWAY_TIMEOUT
+            ),
+            _ => false,
+        }
+    }
+
+    pub(super) fn from_response(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Self {
+        let error = serde_json::from_slice::<LemmyErrorType>(body);
+
+        if status == StatusCode::TOO_MANY_REQUESTS
+            || matches!(error, Ok(LemmyErrorType::RateLimitError))
+        {
+            // Only the delay-seconds form is handled, it's the only one Lemmy and nginx send.
+            let retry_after = headers
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok()?.trim().parse().ok())
+                .map(Duration::from_secs);
+            return Self::RateLimited { retry_after };
+        }
+
+        match error {
+            Ok(error) => Self::Api(error),
+            Err(_) => Self::Http {
+                status,
+                body: String::from_utf8_lossy(body).into_owned(),
+            },
+        }
+    }
+}
+
+impl Display for ClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientError::Timeout => write!(f, "request timed out"),
+            ClientError::Network(e) => write!(f, "network error: {e}"),
+            ClientError::RateLimited { .. } => write!(f, "instance is rate limiting requests"),
+            ClientError::Api(e) => {
+                // Lemmy identifies its errors by their snake_case name.
+                let name = serde_json::to_value(e)
+                    .ok()
+                    .and_then(|value| value.get("error")?.as_str().map(str::to_string))
+                    .unwrap_or_else(|| e.to_string());
+                write!(f, "instance returned an error: {name}")
+            }
+            ClientError::Http { status, .. } => write!(f, "instance returned {status}"),
+            ClientError::Decode(e) => write!(f, "unexpected response: {e}"),
+            ClientError::Other(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(value: reqwest::Error) -> Self {
+        if value.is_timeout() {
+            Self::Timeout
+        } else if value.is_decode() {
+            Self::Decode(value.to_string())
+        } else {
+            Self::Network(value.to_string())
+        }
+    }
+}
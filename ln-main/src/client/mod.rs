@@ -0,0 +1,629 @@
+mod auth;
+mod budget;
+mod error;
+mod recorder;
+mod v3;
+mod v4;
+mod version;
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        RwLock,
+    },
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use lemmy_api_common::{
+    comment::{GetComments, GetCommentsResponse, SaveComment},
+    community::{
+        BlockCommunity, BlockCommunityResponse, CommunityResponse, FollowCommunity, GetCommunity,
+        GetCommunityResponse, ListCommunities,
+    },
+    person::{
+        BlockPerson, BlockPersonResponse, GetPersonDetails, GetPersonDetailsResponse, Login,
+        LoginResponse,
+    },
+    post::{
+        CreatePostLike, GetPost, GetPostResponse, GetPosts, GetPostsResponse, MarkPostAsRead,
+        SavePost,
+    },
+    private_message::CreatePrivateMessage,
+    site::{GetSiteResponse, Search, SearchResponse},
+};
+use ln_config::Network;
+use reqwest::{Certificate, Client, Method, Proxy, RequestBuilder};
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Serialize,
+};
+use tracing::warn;
+
+use auth::Session;
+use budget::RequestBudget;
+pub use error::{ClientError, ClientResult};
+pub use recorder::Recording;
+use recorder::{RawResponse, Recorder};
+use version::ApiVersion;
+
+use crate::types::{
+    Comment, CommunityDetails, CommunityInfo, PersonDetails, Post, PostsPage, SearchResults,
+    SiteInfo,
+};
+
+/// Lemmy's rate limiter bucket for most of the API.
+const MESSAGE_BUCKET: &str = "message";
+
+/// The bucket of Lemmy's rate limiter that `path` counts against.
+fn limiter_bucket(path: &str) -> &'static str {
+    match path {
+        "search" => "search",
+        _ => MESSAGE_BUCKET,
+    }
+}
+
+/// Thin wrapper around the Lemmy HTTP API.
+///
+/// Every endpoint Lemmynator talks to has its own method here, so the rest of
+/// the app never has to build URLs or touch `reqwest` directly.
+pub struct LemmyClient {
+    http: Client,
+    base_url: String,
+    jwt: RwLock<Option<String>>,
+    session: RwLock<Option<Session>>,
+    relogin_lock: tokio::sync::Mutex<()>,
+    api_budget: RequestBudget,
+    image_budget: RequestBudget,
+    offline: AtomicBool,
+    recorder: Option<Recorder>,
+    api_version: RwLock<ApiVersion>,
+}
+
+impl LemmyClient {
+    pub fn new(
+        base_url: impl Into<String>,
+        network: &Network,
+        recording: Option<Recording>,
+    ) -> Result<Self> {
+        let http = Self::http_client(network)?;
+        let recorder = recording.map(Recorder::new).transpose()?;
+
+        Ok(Self {
+            http,
+            base_url: base_url.into(),
+            jwt: RwLock::new(None),
+            session: RwLock::new(None),
+            relogin_lock: tokio::sync::Mutex::new(()),
+            api_budget: RequestBudget::new(
+                network.max_concurrent_requests,
+                network.requests_per_second,
+                network.max_retries,
+            ),
+            // Images are usually served by pict-rs or other hosts, which have
+            // limits of their own rather than Lemmy's.
+            image_budget: RequestBudget::new(
+                network.max_concurrent_images,
+                network.images_per_second,
+                network.max_retries,
+            ),
+            offline: AtomicBool::new(false),
+            recorder,
+            api_version: RwLock::new(ApiVersion::V3),
+        })
+    }
+
+    /// Builds the HTTP client shared by API requests and image downloads.
+    fn http_client(network: &Network) -> Result<Client> {
+        let user_agent = network
+            .user_agent
+            .clone()
+            .unwrap_or_else(|| format!("lemmynator/{}", env!("CARGO_PKG_VERSION")));
+
+        let mut builder = Client::builder()
+            .user_agent(user_agent)
+            .connect_timeout(Duration::from_secs(network.connect_timeout))
+            .read_timeout(Duration::from_secs(network.read_timeout));
+
+        if let Some(proxy) = &network.proxy {
+            let proxy = Proxy::all(proxy).with_context(|| format!("invalid proxy {proxy}"))?;
+            builder = builder.proxy(proxy);
+        }
+
+        if let Some(ca_bundle) = &network.ca_bundle {
+            let pem = std::fs::read(ca_bundle)
+                .with_context(|| format!("failed to read {}", ca_bundle.display()))?;
+            let certificates = Certificate::from_pem_bundle(&pem)
+                .with_context(|| format!("{} is not a PEM bundle", ca_bundle.display()))?;
+            for certificate in certificates {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+
+        Ok(builder.build()?)
+    }
+
+    /// Whether the last API request failed because the instance couldn't be reached.
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::Relaxed)
+    }
+
+    fn jwt(&self) -> Option<String> {
+        self.jwt.read().unwrap().clone()
+    }
+
+    fn set_jwt(&self, jwt: Option<String>) {
+        *self.jwt.write().unwrap() = jwt;
+    }
+
+    pub async fn login(&self, req: &Login) -> ClientResult<LoginResponse> {
+        let path = match self.api_version() {
+            ApiVersion::V3 => "user/login",
+            ApiVersion::V4 => "account/auth/login",
+        };
+        // Never goes through `request`, as a failed login must not trigger another login.
+        self.send(Method::POST, path, req, None).await
+    }
+
+    async fn invalidate_jwt(&self) -> ClientResult<()> {
+        let path = match self.api_version() {
+            ApiVersion::V3 => "user/logout",
+            ApiVersion::V4 => "account/auth/logout",
+        };
+        let jwt = self.jwt();
+        let _: IgnoredAny = self.send(Method::POST, path, &(), jwt.as_deref()).await?;
+        Ok(())
+    }
+
+    pub async fn get_posts(&self, req: &GetPosts) -> ClientResult<PostsPage> {
+        match self.api_version() {
+            ApiVersion::V3 => {
+                let res: GetPostsResponse = self.request(Method::GET, "post/list", req).await?;
+                Ok(res.into())
+            }
+            // Saved posts are listed along with the saved comments.
+            ApiVersion::V4 if req.saved_only == Some(true) => {
+                let res: v4::Paged<v4::Content> = self
+                    .request(Method::GET, "account/saved", &v4::ListSaved::posts(req))
+                    .await?;
+                Ok(res.into())
+            }
+            ApiVersion::V4 => {
+                let res: v4::Paged<v4::PostView> = self
+                    .request(Method::GET, "post/list", &v4::ListPosts::from(req))
+                    .await?;
+                Ok(res.into())
+            }
+        }
+    }
+
+    pub async fn get_post(&self, req: &GetPost) -> ClientResult<Post> {
+        match self.api_version() {
+            ApiVersion::V3 => {
+                let res: GetPostResponse = self.request(Method::GET, "post", req).await?;
+                Ok(res.post_view.into())
+            }
+            ApiVersion::V4 => {
+                let res: v4::GetPostResponse = self.request(Method::GET, "post", req).await?;
+                Ok(res.post_view.into())
+            }
+        }
+    }
+
+    pub async fn like_post(&self, req: &CreatePostLike) -> ClientResult<()> {
+        let _: IgnoredAny = match self.api_version() {
+            ApiVersion::V3 => self.request(Method::POST, "post/like", req).await?,
+            ApiVersion::V4 => {
+                self.request(Method::POST, "post/like", &v4::LikePost::from(req))
+                    .await?
+            }
+        };
+        Ok(())
+    }
+
+    pub async fn save_post(&self, req: &SavePost) -> ClientResult<()> {
+        let _: IgnoredAny = self.request(Method::PUT, "post/save", req).await?;
+        Ok(())
+    }
+
+    pub async fn mark_post_as_read(&self, req: &MarkPostAsRead) -> ClientResult<()> {
+        let _: IgnoredAny = match self.api_version() {
+            ApiVersion::V3 => self.request(Method::POST, "post/mark_as_read", req).await?,
+            ApiVersion::V4 => {
+                self.request(
+                    Method::POST,
+                    "post/mark_as_read",
+                    &v4::MarkPostRead::from(req),
+                )
+                .await?
+            }
+        };
+        Ok(())
+    }
+
+    pub async fn save_comment(&self, req: &SaveComment) -> ClientResult<()> {
+        let _: IgnoredAny = self.request(Method::PUT, "comment/save", req).await?;
+        Ok(())
+    }
+
+    pub async fn get_comments(&self, req: &GetComments) -> ClientResult<Vec<Comment>> {
+        match self.api_version() {
+            ApiVersion::V3 => {
+                let res: GetCommentsResponse =
+                    self.request(Method::GET, "comment/list", req).await?;
+                Ok(res.comments.into_iter().map(Comment::from).collect())
+            }
+            ApiVersion::V4 if req.saved_only == Some(true) => {
+                let res = self
+                    .request(
+                        Method::GET,
+                        "account/saved",
+                        &v4::ListSaved::comments(req.limit),
+                    )
+                    .await?;
+                Ok(v4::comments(res))
+            }
+            ApiVersion::V4 => {
+                let res: v4::Paged<v4::CommentView> =
+                    self.request(Method::GET, "comment/list", req).await?;
+                Ok(res.items())
+            }
+        }
+    }
+
+    pub async fn get_community(&self, req: &GetCommunity) -> ClientResult<CommunityDetails> {
+        match self.api_version() {
+            ApiVersion::V3 => {
+                let res: GetCommunityResponse = self.request(Method::GET, "community", req).await?;
+                Ok(res.into())
+            }
+            ApiVersion::V4 => {
+                let res: v4::GetCommunityResponse =
+                    self.request(Method::GET, "community", req).await?;
+                Ok(res.into())
+            }
+        }
+    }
+
+    pub async fn follow_community(&self, req: &FollowCommunity) -> ClientResult<CommunityInfo> {
+        match self.api_version() {
+            ApiVersion::V3 => {
+                let res: CommunityResponse =
+                    self.request(Method::POST, "community/follow", req).await?;
+                Ok(res.community_view.into())
+            }
+            ApiVersion::V4 => {
+                let res: v4::CommunityResponse =
+                    self.request(Method::POST, "community/follow", req).await?;
+                Ok(res.into())
+            }
+        }
+    }
+
+    pub async fn list_communities(
+        &self,
+        req: &ListCommunities,
+    ) -> ClientResult<Vec<CommunityInfo>> {
+        match self.api_version() {
+            ApiVersion::V3 => {
+                let res = self.request(Method::GET, "community/list", req).await?;
+                Ok(v3::communities(res))
+            }
+            ApiVersion::V4 => {
+                let res: v4::Paged<v4::CommunityView> = self
+                    .request(
+                        Method::GET,
+                        "community/list",
+                        &v4::ListCommunitiesV4::from(req),
+                    )
+                    .await?;
+                Ok(res.items())
+            }
+        }
+    }
+
+    /// The community as it is once blocked or unblocked.
+    pub async fn block_community(&self, req: &BlockCommunity) -> ClientResult<CommunityInfo> {
+        match self.api_version() {
+            ApiVersion::V3 => {
+                let res: BlockCommunityResponse =
+                    self.request(Method::POST, "community/block", req).await?;
+                let mut info = CommunityInfo::from(res.community_view);
+                info.blocked = res.blocked;
+                Ok(info)
+            }
+            ApiVersion::V4 => {
+                let res: v4::CommunityResponse = self
+                    .request(Method::POST, "account/block/community", req)
+                    .await?;
+                Ok(res.into())
+            }
+        }
+    }
+
+    pub async fn get_person_details(&self, req: &GetPersonDetails) -> ClientResult<PersonDetails> {
+        match self.api_version() {
+            ApiVersion::V3 => {
+                let res: GetPersonDetailsResponse = self.request(Method::GET, "user", req).await?;
+                Ok(res.into())
+            }
+            // The person and what they posted come from separate endpoints,
+            // and only the account's own saved content can be listed.
+            ApiVersion::V4 => {
+                let person = self
+                    .request(Method::GET, "person", &v4::GetPerson::from(req))
+                    .await?;
+                let content = if req.saved_only == Some(true) {
+                    self.request(Method::GET, "account/saved", &v4::ListSaved::all(req.limit))
+                        .await?
+                } else {
+                    self.request(
+                        Method::GET,
+                        "person/content",
+                        &v4::ListPersonContent::from(req),
+                    )
+                    .await?
+                };
+                Ok(v4::person_details(person, content))
+            }
+        }
+    }
+
+    /// Whether the person is blocked now.
+    pub async fn block_person(&self, req: &BlockPerson) -> ClientResult<bool> {
+        match self.api_version() {
+            ApiVersion::V3 => {
+                let res: BlockPersonResponse =
+                    self.request(Method::POST, "user/block", req).await?;
+                Ok(res.blocked)
+            }
+            ApiVersion::V4 => {
+                let res: v4::BlockPersonResponse = self
+                    .request(Method::POST, "account/block/person", req)
+                    .await?;
+                Ok(res.blocked.unwrap_or(req.block))
+            }
+        }
+    }
+
+    pub async fn create_private_message(&self, req: &CreatePrivateMessage) -> ClientResult<()> {
+        let _: IgnoredAny = self.request(Method::POST, "private_message", req).await?;
+        Ok(())
+    }
+
+    pub async fn search(&self, req: &Search) -> ClientResult<SearchResults> {
+        match self.api_version() {
+            ApiVersion::V3 => {
+                let res: SearchResponse = self.request(Method::GET, "search", req).await?;
+                Ok(res.into())
+            }
+            ApiVersion::V4 => {
+                let res: v4::SearchResponse = self
+                    .request(Method::GET, "search", &v4::SearchV4::from(req))
+                    .await?;
+                Ok(res.into())
+            }
+        }
+    }
+
+    pub async fn get_site(&self) -> ClientResult<SiteInfo> {
+        match self.api_version() {
+            ApiVersion::V3 => {
+                let res: GetSiteResponse = self.request(Method::GET, "site", &()).await?;
+                Ok(res.into())
+            }
+            // The account's details are sent on their own.
+            ApiVersion::V4 => {
+                let site = self.request(Method::GET, "site", &()).await?;
+                let my_user = if self.is_authenticated() {
+                    Some(self.request(Method::GET, "account", &()).await?)
+                } else {
+                    None
+                };
+                Ok(v4::site_info(site, my_user))
+            }
+        }
+    }
+
+    /// Unread replies, mentions and private messages.
+    pub async fn unread_count(&self) -> ClientResult<i64> {
+        match self.api_version() {
+            ApiVersion::V3 => {
+                let res = self.request(Method::GET, "user/unread_count", &()).await?;
+                Ok(v3::unread_count(res))
+            }
+            ApiVersion::V4 => {
+                let res: v4::UnreadCount = self
+                    .request(Method::GET, "account/unread_count", &())
+                    .await?;
+                Ok(res.count)
+            }
+        }
+    }
+
+    /// Downloads an image (thumbnail, avatar, ...) from an arbitrary URL.
+    ///
+    /// The authorization header is deliberately not attached, as these URLs
+    /// often point outside of the instance.
+    pub async fn fetch_image(&self, url: &str) -> ClientResult<Bytes> {
+        // Every host serving images has limits of its own.
+        let host = url::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_default();
+        self.image_budget
+            .run(&host, true, || async {
+                let res = self.execute(self.http.get(url)).await?;
+
+                if res.status.is_success() {
+                    Ok(res.body)
+                } else {
+                    Err(ClientError::from_response(
+                        res.status,
+                        &res.headers,
+                        &res.body,
+                    ))
+                }
+            })
+            .await
+    }
+
+    fn api_version(&self) -> ApiVersion {
+        *self.api_version.read().unwrap()
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!(
+            "{}/api/{}/{}",
+            self.base_url,
+            self.api_version().prefix(),
+            path
+        )
+    }
+
+    /// Fetches JSON from outside of the API, like nodeinfo.
+    async fn get_json<Res: DeserializeOwned>(&self, url: &str) -> ClientResult<Res> {
+        self.api_budget
+            .run(MESSAGE_BUCKET, true, || async {
+                let res = self.execute(self.http.get(url)).await?;
+
+                if res.status.is_success() {
+                    serde_json::from_slice(&res.body)
+                        .map_err(|e| ClientError::Decode(e.to_string()))
+                } else {
+                    Err(ClientError::from_response(
+                        res.status,
+                        &res.headers,
+                        &res.body,
+                    ))
+                }
+            })
+            .await
+    }
+
+    /// Sends an authenticated request, logging in again and replaying it once
+    /// if the instance no longer accepts our token.
+    async fn request<Req, Res>(&self, method: Method, path: &str, params: &Req) -> ClientResult<Res>
+    where
+        Req: Serialize + ?Sized,
+        Res: DeserializeOwned,
+    {
+        let jwt = self.jwt();
+
+        match self
+            .send(method.clone(), path, params, jwt.as_deref())
+            .await
+        {
+            Err(e) if e.is_auth_failure() && self.is_authenticated() => {
+                warn!("{path} was rejected ({e}), logging in again");
+                self.relogin(jwt.as_deref()).await?;
+                let jwt = self.jwt();
+                self.send(method, path, params, jwt.as_deref()).await
+            }
+            res => res,
+        }
+    }
+
+    /// Sends a request within the API budget, retrying it if it failed transiently.
+    async fn send<Req, Res>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &Req,
+        jwt: Option<&str>,
+    ) -> ClientResult<Res>
+    where
+        Req: Serialize + ?Sized,
+        Res: DeserializeOwned,
+    {
+        let idempotent = method == Method::GET;
+        let res = self
+            .api_budget
+            .run(limiter_bucket(path), idempotent, || {
+                self.send_once(method.clone(), path, params, jwt)
+            })
+            .await;
+
+        let offline = matches!(res, Err(ClientError::Timeout | ClientError::Network(_)));
+        self.offline.store(offline, Ordering::Relaxed);
+
+        res
+    }
+
+    async fn send_once<Req, Res>(
+        &self,
+        method: Method,
+        path: &str,
+        params: &Req,
+        jwt: Option<&str>,
+    ) -> ClientResult<Res>
+    where
+        Req: Serialize + ?Sized,
+        Res: DeserializeOwned,
+    {
+        let req = self.http.request(method.clone(), self.endpoint(path));
+        let mut req = with_params(req, &method, params);
+
+        if let Some(jwt) = jwt {
+            req = req.bearer_auth(jwt);
+        }
+
+        let res = self.execute(req).await?;
+
+        if !res.status.is_success() {
+            return Err(ClientError::from_response(
+                res.status,
+                &res.headers,
+                &res.body,
+            ));
+        }
+
+        serde_json::from_slice(&res.body).map_err(|e| ClientError::Decode(e.to_string()))
+    }
+
+    /// Performs the request over the network, or against a recording.
+    async fn execute(&self, req: RequestBuilder) -> ClientResult<RawResponse> {
+        let req = req.build()?;
+
+        let Some(recorder) = &self.recorder else {
+            return self.execute_live(req).await;
+        };
+
+        let key = recorder.key(&req);
+        if recorder.is_replaying() {
+            return recorder.replay(&req, key);
+        }
+
+        let res = self
+            .execute_live(req.try_clone().expect("bodies are never streamed"))
+            .await?;
+        recorder.record(&req, key, &res);
+        Ok(res)
+    }
+
+    async fn execute_live(&self, req: reqwest::Request) -> ClientResult<RawResponse> {
+        let res = self.http.execute(req).await?;
+        let status = res.status();
+        let headers = res.headers().clone();
+        let body = res.bytes().await?;
+
+        Ok(RawResponse {
+            status,
+            headers,
+            body,
+        })
+    }
+}
+
+fn with_params<P: Serialize + ?Sized>(
+    req: RequestBuilder,
+    method: &Method,
+    params: &P,
+) -> RequestBuilder {
+    if method == Method::GET {
+        req.query(params)
+    } else {
+        req.json(params)
+    }
+}
@@ -0,0 +1,329 @@
+use std::{collections::HashMap, fs, path::PathBuf, sync::Mutex};
+
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Method, Request, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use super::{ClientError, ClientResult};
+
+/// Stands in for the token in recorded login responses, so no secrets end up on disk.
+const REDACTED_JWT: &str = "recorded-session";
+
+/// Records every response to a directory, or serves them back from one.
+#[derive(Clone)]
+pub enum Recording {
+    Record(PathBuf),
+    Replay(PathBuf),
+}
+
+impl Recording {
+    fn dir(&self) -> &PathBuf {
+        match self {
+            Recording::Record(dir) | Recording::Replay(dir) => dir,
+        }
+    }
+}
+
+/// A response, whether it came from the network or from a recording.
+pub(super) struct RawResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    method: String,
+    url: String,
+    status: u16,
+    headers: Vec<(String, String)>,
+}
+
+/// Requests are matched by their method, URL and body, never by their
+/// authorization. Identical requests are served in the order they were
+/// recorded, the last recording being repeated once they run out.
+pub(super) struct Recorder {
+    recording: Recording,
+    seen: Mutex<HashMap<u64, usize>>,
+}
+
+impl Recorder {
+    pub fn new(recording: Recording) -> Result<Self> {
+        if let Recording::Record(dir) = &recording {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create {}", dir.display()))?;
+        } else if !recording.dir().is_dir() {
+            anyhow::bail!("there's no recording in {}", recording.dir().display());
+        }
+
+        Ok(Self {
+            recording,
+            seen: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn is_replaying(&self) -> bool {
+        matches!(self.recording, Recording::Replay(_))
+    }
+
+    /// Identifies `req` along with how many times an identical one was made before.
+    ///
+    /// The hash ends up in file names, so it has to stay the same across
+    /// builds, which rules out the standard library's hashers.
+    pub fn key(&self, req: &Request) -> (u64, usize) {
+        let mut hasher = Fnv1a::new();
+        hasher.write(req.method().as_str().as_bytes());
+        hasher.write(b" ");
+        hasher.write(req.url().as_str().as_bytes());
+        // The login body holds the password, which may differ between sessions.
+        if !is_login(req) {
+            if let Some(body) = req.body().and_then(|body| body.as_bytes()) {
+                hasher.write(b"\n");
+                hasher.write(&canonical_body(body));
+            }
+        }
+        let hash = hasher.0;
+
+        let mut seen = self.seen.lock().unwrap();
+        let count = seen.entry(hash).or_default();
+        let key = (hash, *count);
+        *count += 1;
+        key
+    }
+
+    fn path(&self, (hash, n): (u64, usize), extension: &str) -> PathBuf {
+        self.recording
+            .dir()
+            .join(format!("{hash:016x}-{n}.{extension}"))
+    }
+
+    pub fn replay(&self, req: &Request, key: (u64, usize)) -> ClientResult<RawResponse> {
+        let (hash, n) = key;
+        let Some(key) = (0..=n)
+            .rev()
+            .map(|n| (hash, n))
+            .find(|key| self.path(*key, "json").exists())
+        else {
+            return Err(ClientError::Other(format!(
+                "no recorded response for {} {}",
+                req.method(),
+                req.url()
+            )));
+        };
+
+        let read = || -> Result<RawResponse> {
+            let entry: Entry = serde_json::from_slice(&fs::read(self.path(key, "json"))?)?;
+            let body = fs::read(self.path(key, "body"))?;
+
+            let mut headers = HeaderMap::new();
+            for (name, value) in entry.headers {
+                headers.append(HeaderName::try_from(name)?, HeaderValue::try_from(value)?);
+            }
+
+            Ok(RawResponse {
+                status: StatusCode::from_u16(entry.status)?,
+                headers,
+                body: body.into(),
+            })
+        };
+
+        read().map_err(|e| ClientError::Other(format!("broken recording: {e:#}")))
+    }
+
+    pub fn record(&self, req: &Request, key: (u64, usize), res: &RawResponse) {
+        let body = if is_login(req) {
+            redact_jwt(&res.body)
+        } else {
+            res.body.clone()
+        };
+
+        let entry = Entry {
+            method: req.method().to_string(),
+            url: req.url().to_string(),
+            status: res.status.as_u16(),
+            headers: res
+                .headers
+                .iter()
+                .filter_map(|(name, value)| {
+                    Some((name.to_string(), value.to_str().ok()?.to_string()))
+                })
+                .collect(),
+        };
+
+        let write = || -> Result<()> {
+            fs::write(self.path(key, "body"), &body)?;
+            fs::write(self.path(key, "json"), serde_json::to_vec_pretty(&entry)?)?;
+            Ok(())
+        };
+
+        if let Err(e) = write() {
+            warn!("Could not record {} {}: {e:#}", req.method(), req.url());
+        }
+    }
+}
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is guaranteed never to change.
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+/// JSON bodies with their keys sorted, so the field order serde happens to
+/// use doesn't matter. Anything else is taken as is.
+fn canonical_body(body: &[u8]) -> Vec<u8> {
+    serde_json::from_slice::<serde_json::Value>(body)
+        .and_then(|value| serde_json::to_vec(&value))
+        .unwrap_or_else(|_| body.to_vec())
+}
+
+fn is_login(req: &Request) -> bool {
+    req.method() == Method::POST && req.url().path().ends_with("/user/login")
+}
+
+fn redact_jwt(body: &Bytes) -> Bytes {
+    let Ok(mut value) = serde_json::from_slice::<serde_json::Value>(body) else {
+        return body.clone();
+    };
+
+    if let Some(jwt) = value.get_mut("jwt").filter(|jwt| jwt.is_string()) {
+        *jwt = REDACTED_JWT.into();
+    }
+
+    serde_json::to_vec(&value).map_or_else(|_| body.clone(), Bytes::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::{header::SET_COOKIE, Url};
+    use tempfile::TempDir;
+
+    use super::*;
+
+    /// A directory for a recording, not created yet, removed along with the guard.
+    fn dir() -> (TempDir, PathBuf) {
+        let guard = tempfile::tempdir().unwrap();
+        let dir = guard.path().join("recording");
+        (guard, dir)
+    }
+
+    fn request(method: Method, url: &str, body: Option<&str>) -> Request {
+        let mut req = Request::new(method, Url::parse(url).unwrap());
+        *req.body_mut() = body.map(|body| body.to_string().into());
+        req
+    }
+
+    #[test]
+    fn keys_are_stable() {
+        let (_guard, dir) = dir();
+        let recorder = Recorder::new(Recording::Record(dir)).unwrap();
+        let req = request(
+            Method::GET,
+            "https://lemmy.example/api/v3/post/list?sort=Hot",
+            None,
+        );
+
+        // Recordings made by older builds must keep matching.
+        assert_eq!(recorder.key(&req), (0x9c5b_fb40_55ba_c2fc, 0));
+        assert_eq!(recorder.key(&req), (0x9c5b_fb40_55ba_c2fc, 1));
+    }
+
+    #[test]
+    fn hashes_fnv1a() {
+        let mut hasher = Fnv1a::new();
+        hasher.write(b"a");
+        assert_eq!(hasher.0, 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn keys_ignore_field_order_but_not_values() {
+        let (_guard, dir) = dir();
+        let recorder = Recorder::new(Recording::Record(dir)).unwrap();
+        let url = "https://lemmy.example/api/v3/post/like";
+        let key = |body| recorder.key(&request(Method::POST, url, Some(body))).0;
+
+        assert_eq!(
+            key(r#"{"post_id":1,"score":1}"#),
+            key(r#"{"score":1,"post_id":1}"#)
+        );
+        assert_ne!(
+            key(r#"{"post_id":1,"score":1}"#),
+            key(r#"{"post_id":1,"score":-1}"#)
+        );
+    }
+
+    #[test]
+    fn replays_what_was_recorded() {
+        let (_guard, dir) = dir();
+        let req = || request(Method::GET, "https://lemmy.example/api/v3/site", None);
+
+        let mut headers = HeaderMap::new();
+        headers.append(SET_COOKIE, HeaderValue::from_static("a=1"));
+        headers.append(SET_COOKIE, HeaderValue::from_static("b=2"));
+        let recorder = Recorder::new(Recording::Record(dir.clone())).unwrap();
+        for body in ["first", "second"] {
+            let res = RawResponse {
+                status: StatusCode::OK,
+                headers: headers.clone(),
+                body: Bytes::from(body),
+            };
+            recorder.record(&req(), recorder.key(&req()), &res);
+        }
+
+        let replayer = Recorder::new(Recording::Replay(dir)).unwrap();
+        let replay = || replayer.replay(&req(), replayer.key(&req())).unwrap();
+        let first = replay();
+        assert_eq!(first.status, StatusCode::OK);
+        assert_eq!(first.headers.get_all(SET_COOKIE).iter().count(), 2);
+        assert_eq!(first.body, "first");
+        assert_eq!(replay().body, "second");
+        // Once the recordings run out the last one keeps being served.
+        assert_eq!(replay().body, "second");
+    }
+
+    #[test]
+    fn redacts_login_tokens() {
+        let (_guard, dir) = dir();
+        let login = || {
+            request(
+                Method::POST,
+                "https://lemmy.example/api/v3/user/login",
+                Some(r#"{"username_or_email":"me","password":"hunter2"}"#),
+            )
+        };
+        let recorder = Recorder::new(Recording::Record(dir.clone())).unwrap();
+        let res = RawResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: Bytes::from(r#"{"jwt":"secret"}"#),
+        };
+        recorder.record(&login(), recorder.key(&login()), &res);
+
+        let replayer = Recorder::new(Recording::Replay(dir)).unwrap();
+        let mut other_password = login();
+        *other_password.body_mut() = Some(r#"{"password":"other"}"#.into());
+        let key = replayer.key(&other_password);
+        let replayed = replayer.replay(&other_password, key).unwrap();
+        assert_eq!(replayed.body, r#"{"jwt":"recorded-session"}"#);
+    }
+
+    #[test]
+    fn refuses_to_replay_nothing() {
+        let (_guard, dir) = dir();
+        assert!(Recorder::new(Recording::Replay(dir)).is_err());
+    }
+}
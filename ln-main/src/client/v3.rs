@@ -0,0 +1,241 @@
+//! Lemmy 0.19's API (v3), whose requests are the ones from `lemmy_api_common`.
+//!
+//! Its responses are decoded with `lemmy_api_common` as well and turned into
+//! Lemmynator's own types here.
+
+use lemmy_api_common::{
+    community::{GetCommunityResponse, ListCommunitiesResponse},
+    lemmy_db_schema::{
+        newtypes::DbUrl,
+        source::{community, person},
+        ListingType,
+    },
+    lemmy_db_views::structs::{CommentView, PostView},
+    lemmy_db_views_actor::structs::{CommunityView, PersonView},
+    person::{GetPersonDetailsResponse, GetUnreadCountResponse},
+    post::GetPostsResponse,
+    site::{GetSiteResponse, SearchResponse},
+};
+
+use crate::types::{
+    Comment, Community, CommunityDetails, CommunityInfo, Person, PersonDetails, PersonInfo, Post,
+    PostsPage, SearchResults, SiteInfo,
+};
+
+fn host(actor_id: &DbUrl) -> String {
+    actor_id.inner().host_str().unwrap_or_default().to_string()
+}
+
+impl From<person::Person> for Person {
+    fn from(person: person::Person) -> Self {
+        Self {
+            id: person.id,
+            host: host(&person.actor_id),
+            name: person.name,
+            display_name: person.display_name,
+            avatar: person.avatar.map(|avatar| avatar.to_string()),
+        }
+    }
+}
+
+impl From<community::Community> for Community {
+    fn from(community: community::Community) -> Self {
+        Self {
+            id: community.id,
+            host: host(&community.actor_id),
+            name: community.name,
+            title: community.title,
+            icon: community.icon.map(|icon| icon.to_string()),
+            nsfw: community.nsfw,
+        }
+    }
+}
+
+impl From<PostView> for Post {
+    fn from(view: PostView) -> Self {
+        let post = view.post;
+        Self {
+            id: post.id,
+            name: post.name,
+            body: post.body,
+            embed_description: post.embed_description,
+            url: post.url.map(|url| url.to_string()),
+            thumbnail_url: post.thumbnail_url.map(|url| url.to_string()),
+            nsfw: post.nsfw,
+            featured_local: post.featured_local,
+            featured_community: post.featured_community,
+            creator: view.creator.into(),
+            community: view.community.into(),
+            score: view.counts.score,
+            upvotes: view.counts.upvotes,
+            downvotes: view.counts.downvotes,
+            comments: view.counts.comments,
+            my_vote: view.my_vote,
+            saved: view.saved,
+            read: view.read,
+            unread_comments: view.unread_comments,
+        }
+    }
+}
+
+impl From<CommentView> for Comment {
+    fn from(view: CommentView) -> Self {
+        Self {
+            id: view.comment.id,
+            content: view.comment.content,
+            path: view.comment.path,
+            post_id: view.post.id,
+            post_name: view.post.name,
+            creator: view.creator.into(),
+            community: view.community.into(),
+            saved: view.saved,
+        }
+    }
+}
+
+impl From<GetPostsResponse> for PostsPage {
+    fn from(res: GetPostsResponse) -> Self {
+        Self {
+            posts: res.posts.into_iter().map(Post::from).collect(),
+            next_page: res.next_page,
+        }
+    }
+}
+
+impl From<CommunityView> for CommunityInfo {
+    fn from(view: CommunityView) -> Self {
+        Self {
+            description: view.community.description.clone(),
+            community: view.community.into(),
+            subscribers: view.counts.subscribers,
+            users_active_day: view.counts.users_active_day,
+            users_active_week: view.counts.users_active_week,
+            subscribed: view.subscribed,
+            blocked: view.blocked,
+        }
+    }
+}
+
+pub(super) fn communities(res: ListCommunitiesResponse) -> Vec<CommunityInfo> {
+    res.communities
+        .into_iter()
+        .map(CommunityInfo::from)
+        .collect()
+}
+
+impl From<GetCommunityResponse> for CommunityDetails {
+    fn from(res: GetCommunityResponse) -> Self {
+        Self {
+            info: res.community_view.into(),
+            moderators: res
+                .moderators
+                .into_iter()
+                .map(|view| view.moderator.into())
+                .collect(),
+        }
+    }
+}
+
+impl From<PersonView> for PersonInfo {
+    fn from(view: PersonView) -> Self {
+        let person = view.person;
+        Self {
+            banner: person.banner.as_ref().map(|banner| banner.to_string()),
+            bio: person.bio.clone(),
+            published: person.published,
+            bot_account: person.bot_account,
+            banned: person.banned,
+            is_admin: view.is_admin,
+            post_count: view.counts.post_count,
+            comment_count: view.counts.comment_count,
+            person: person.into(),
+        }
+    }
+}
+
+impl From<GetPersonDetailsResponse> for PersonDetails {
+    fn from(res: GetPersonDetailsResponse) -> Self {
+        Self {
+            info: res.person_view.into(),
+            posts: res.posts.into_iter().map(Post::from).collect(),
+            comments: res.comments.into_iter().map(Comment::from).collect(),
+            moderates: res
+                .moderates
+                .into_iter()
+                .map(|view| view.community.into())
+                .collect(),
+        }
+    }
+}
+
+impl From<SearchResponse> for SearchResults {
+    fn from(res: SearchResponse) -> Self {
+        Self {
+            posts: res.posts.into_iter().map(Post::from).collect(),
+            comments: res.comments.into_iter().map(Comment::from).collect(),
+            communities: res
+                .communities
+                .into_iter()
+                .map(CommunityInfo::from)
+                .collect(),
+            users: res.users.into_iter().map(PersonInfo::from).collect(),
+        }
+    }
+}
+
+pub(super) fn unread_count(res: GetUnreadCountResponse) -> i64 {
+    res.replies + res.mentions + res.private_messages
+}
+
+impl From<GetSiteResponse> for SiteInfo {
+    fn from(res: GetSiteResponse) -> Self {
+        let site = res.site_view.site;
+        let local_site = res.site_view.local_site;
+
+        let mut info = Self {
+            name: site.name,
+            icon: site.icon.map(|icon| icon.to_string()),
+            default_listing_type: local_site.default_post_listing_type,
+            default_sort_type: local_site.default_sort_type,
+            ..Self::default()
+        };
+
+        if let Some(my_user) = res.my_user {
+            let local_user = my_user.local_user_view.local_user;
+            info.default_listing_type = local_user.default_listing_type;
+            info.default_sort_type = local_user.default_sort_type;
+            info.show_nsfw = local_user.show_nsfw;
+            info.show_read_posts = local_user.show_read_posts;
+            info.is_admin = local_user.admin;
+            info.person_id = Some(my_user.local_user_view.person.id);
+
+            info.subscriptions = my_user
+                .follows
+                .into_iter()
+                .map(|follow| follow.community.id)
+                .collect();
+            info.blocked_communities = my_user
+                .community_blocks
+                .into_iter()
+                .map(|block| block.community.id)
+                .collect();
+            info.blocked_people = my_user
+                .person_blocks
+                .into_iter()
+                .map(|block| block.target.id)
+                .collect();
+            info.blocked_instances = my_user
+                .instance_blocks
+                .into_iter()
+                .map(|block| block.instance.id)
+                .collect();
+        }
+
+        // Moderator views aren't one of the tabs.
+        if info.default_listing_type == ListingType::ModeratorView {
+            info.default_listing_type = ListingType::Local;
+        }
+
+        info
+    }
+}
@@ -0,0 +1,1141 @@
+//! Lemmy 1.0's API (v4), for the endpoints Lemmynator calls.
+//!
+//! Requests are built from the 0.19 ones the rest of the app uses, and
+//! responses are decoded into the few fields Lemmynator reads before being
+//! turned into its own types. Anything else v4 sends is ignored, so fields
+//! added or reshaped later only matter once Lemmynator starts reading them.
+
+use chrono::{DateTime, Utc};
+use lemmy_api_common::{
+    community::ListCommunities,
+    lemmy_db_schema::{
+        newtypes::{CommentId, CommunityId, InstanceId, PersonId, PostId},
+        ListingType, SearchType, SortType, SubscribedType,
+    },
+    lemmy_db_views::structs::PaginationCursor,
+    person::GetPersonDetails,
+    post::{CreatePostLike, GetPosts, MarkPostAsRead},
+    site::Search,
+};
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+
+use crate::types::{
+    self, CommunityDetails, CommunityInfo, PersonDetails, PersonInfo, PostsPage, SearchResults,
+    SiteInfo,
+};
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+/// The `Top*` sorts along with the time range v4 expresses them by.
+const TOP_RANGES: [(SortType, u64); 10] = [
+    (SortType::TopHour, HOUR),
+    (SortType::TopSixHour, 6 * HOUR),
+    (SortType::TopTwelveHour, 12 * HOUR),
+    (SortType::TopDay, DAY),
+    (SortType::TopWeek, 7 * DAY),
+    (SortType::TopMonth, 30 * DAY),
+    (SortType::TopThreeMonths, 90 * DAY),
+    (SortType::TopSixMonths, 180 * DAY),
+    (SortType::TopNineMonths, 270 * DAY),
+    (SortType::TopYear, 365 * DAY),
+];
+
+/// v4 replaced the `Top*` sorts by `Top` with a time range.
+fn post_sort(sort: SortType) -> (String, Option<u64>) {
+    if sort == SortType::TopAll {
+        return ("Top".into(), None);
+    }
+    match TOP_RANGES.iter().find(|(top, _)| *top == sort) {
+        Some((_, range)) => ("Top".into(), Some(*range)),
+        None => (sort.to_string(), None),
+    }
+}
+
+/// The 0.19 sort closest to a v4 sort with its time range.
+fn sort_from_v4(sort: Option<&str>, range: Option<u64>) -> SortType {
+    match sort {
+        Some("Top") => range
+            .and_then(|range| TOP_RANGES.iter().find(|(_, top)| *top == range))
+            .map_or(SortType::TopAll, |(top, _)| *top),
+        Some(sort) => sort.parse().unwrap_or(SortType::Hot),
+        None => SortType::Hot,
+    }
+}
+
+/// v4 knows listings 0.19 doesn't, those fall back to the local one, as
+/// does the moderator view, which isn't one of the tabs.
+fn listing_type_from_v4(listing_type: Option<&str>) -> ListingType {
+    match listing_type {
+        Some("All") => ListingType::All,
+        Some("Subscribed") => ListingType::Subscribed,
+        _ => ListingType::Local,
+    }
+}
+
+/// Communities got their own sorts, this picks the closest one.
+fn community_sort(sort: SortType) -> &'static str {
+    match sort {
+        SortType::Hot => "Hot",
+        SortType::New => "New",
+        SortType::Old => "Old",
+        SortType::Active => "ActiveWeekly",
+        SortType::MostComments | SortType::NewComments => "Comments",
+        SortType::TopHour
+        | SortType::TopSixHour
+        | SortType::TopTwelveHour
+        | SortType::TopDay
+        | SortType::TopWeek
+        | SortType::TopMonth
+        | SortType::TopThreeMonths
+        | SortType::TopSixMonths
+        | SortType::TopNineMonths
+        | SortType::TopYear
+        | SortType::TopAll => "Subscribers",
+        SortType::Controversial | SortType::Scaled => "Hot",
+    }
+}
+
+/// `GET post/list`.
+#[derive(Serialize)]
+pub(super) struct ListPosts {
+    type_: Option<ListingType>,
+    sort: Option<String>,
+    time_range_seconds: Option<u64>,
+    community_id: Option<CommunityId>,
+    show_read: Option<bool>,
+    page_cursor: Option<PaginationCursor>,
+    limit: Option<i64>,
+}
+
+impl From<&GetPosts> for ListPosts {
+    fn from(req: &GetPosts) -> Self {
+        let (sort, time_range_seconds) = req.sort.map(post_sort).unzip();
+        Self {
+            type_: req.type_,
+            sort,
+            time_range_seconds: time_range_seconds.flatten(),
+            community_id: req.community_id,
+            show_read: req.show_read,
+            page_cursor: req.page_cursor.clone(),
+            limit: req.limit,
+        }
+    }
+}
+
+/// `GET account/saved`, what used to be asked for with `saved_only`.
+#[derive(Serialize)]
+pub(super) struct ListSaved {
+    type_: &'static str,
+    page_cursor: Option<PaginationCursor>,
+    limit: Option<i64>,
+}
+
+impl ListSaved {
+    pub(super) fn posts(req: &GetPosts) -> Self {
+        Self {
+            type_: "Posts",
+            page_cursor: req.page_cursor.clone(),
+            limit: req.limit,
+        }
+    }
+
+    pub(super) fn comments(limit: Option<i64>) -> Self {
+        Self {
+            type_: "Comments",
+            page_cursor: None,
+            limit,
+        }
+    }
+
+    pub(super) fn all(limit: Option<i64>) -> Self {
+        Self {
+            type_: "All",
+            page_cursor: None,
+            limit,
+        }
+    }
+}
+
+/// `GET person`, which no longer sends what the person posted.
+#[derive(Serialize)]
+pub(super) struct GetPerson {
+    person_id: Option<PersonId>,
+    username: Option<String>,
+}
+
+impl From<&GetPersonDetails> for GetPerson {
+    fn from(req: &GetPersonDetails) -> Self {
+        Self {
+            person_id: req.person_id,
+            username: req.username.clone(),
+        }
+    }
+}
+
+/// `GET person/content`, the posts and comments that used to come with the person.
+#[derive(Serialize)]
+pub(super) struct ListPersonContent {
+    person_id: Option<PersonId>,
+    username: Option<String>,
+    limit: Option<i64>,
+}
+
+impl From<&GetPersonDetails> for ListPersonContent {
+    fn from(req: &GetPersonDetails) -> Self {
+        Self {
+            person_id: req.person_id,
+            username: req.username.clone(),
+            limit: req.limit,
+        }
+    }
+}
+
+/// `GET community/list`.
+#[derive(Serialize)]
+pub(super) struct ListCommunitiesV4 {
+    type_: Option<ListingType>,
+    sort: Option<&'static str>,
+    show_nsfw: Option<bool>,
+    limit: Option<i64>,
+}
+
+impl From<&ListCommunities> for ListCommunitiesV4 {
+    fn from(req: &ListCommunities) -> Self {
+        Self {
+            type_: req.type_,
+            sort: req.sort.map(community_sort),
+            show_nsfw: req.show_nsfw,
+            limit: req.limit,
+        }
+    }
+}
+
+/// `GET search`, which takes a `search_term`, sorts by a few sorts of its own
+/// and has no `Url` type.
+#[derive(Serialize)]
+pub(super) struct SearchV4 {
+    search_term: String,
+    type_: Option<SearchType>,
+    post_url_only: Option<bool>,
+    sort: Option<String>,
+    time_range_seconds: Option<u64>,
+    listing_type: Option<ListingType>,
+    community_id: Option<CommunityId>,
+    creator_id: Option<PersonId>,
+    limit: Option<i64>,
+}
+
+impl From<&Search> for SearchV4 {
+    fn from(req: &Search) -> Self {
+        let (type_, post_url_only) = match req.type_ {
+            Some(SearchType::Url) => (Some(SearchType::Posts), Some(true)),
+            type_ => (type_, None),
+        };
+        let (sort, time_range_seconds) = match req.sort {
+            None => (None, None),
+            Some(sort @ (SortType::New | SortType::Old)) => (Some(sort.to_string()), None),
+            Some(sort) => match post_sort(sort) {
+                (top, range) if top == "Top" => (Some(top), range),
+                _ => (Some("Top".into()), None),
+            },
+        };
+
+        Self {
+            search_term: req.q.clone(),
+            type_,
+            post_url_only,
+            sort,
+            time_range_seconds,
+            listing_type: req.listing_type,
+            community_id: req.community_id,
+            creator_id: req.creator_id,
+            limit: req.limit,
+        }
+    }
+}
+
+/// `POST post/like`, with an upvote, a downvote or neither instead of a score.
+#[derive(Serialize)]
+pub(super) struct LikePost {
+    post_id: PostId,
+    is_upvote: Option<bool>,
+}
+
+impl From<&CreatePostLike> for LikePost {
+    fn from(req: &CreatePostLike) -> Self {
+        let is_upvote = match req.score {
+            1 => Some(true),
+            -1 => Some(false),
+            _ => None,
+        };
+        Self {
+            post_id: req.post_id,
+            is_upvote,
+        }
+    }
+}
+
+/// `POST post/mark_as_read`, for a single post, `post_ids` went to `mark_as_read/many`.
+#[derive(Serialize)]
+pub(super) struct MarkPostRead {
+    post_id: Option<PostId>,
+    read: bool,
+}
+
+impl From<&MarkPostAsRead> for MarkPostRead {
+    fn from(req: &MarkPostAsRead) -> Self {
+        Self {
+            post_id: req.post_ids.first().copied(),
+            read: req.read,
+        }
+    }
+}
+
+/// The instance something is from, taken from its ActivityPub id.
+fn host(ap_id: &str) -> String {
+    url::Url::parse(ap_id)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Paged responses keep their entries in `items`.
+#[derive(Deserialize)]
+pub(super) struct Paged<T> {
+    items: Vec<T>,
+    next_page: Option<PaginationCursor>,
+}
+
+impl<T> Paged<T> {
+    pub(super) fn items<U: From<T>>(self) -> Vec<U> {
+        self.items.into_iter().map(U::from).collect()
+    }
+}
+
+#[derive(Deserialize)]
+struct Person {
+    id: PersonId,
+    name: String,
+    display_name: Option<String>,
+    ap_id: String,
+    avatar: Option<String>,
+    banner: Option<String>,
+    bio: Option<String>,
+    #[serde(default)]
+    bot_account: bool,
+    published_at: DateTime<Utc>,
+    // Aggregates now live on the person itself, as they do on the rest.
+    #[serde(default)]
+    post_count: i64,
+    #[serde(default)]
+    comment_count: i64,
+}
+
+impl From<Person> for types::Person {
+    fn from(person: Person) -> Self {
+        Self {
+            id: person.id,
+            host: host(&person.ap_id),
+            name: person.name,
+            display_name: person.display_name,
+            avatar: person.avatar,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Community {
+    id: CommunityId,
+    name: String,
+    title: String,
+    ap_id: String,
+    icon: Option<String>,
+    #[serde(default)]
+    nsfw: bool,
+    /// What 0.19 called the description, which is now a short summary.
+    sidebar: Option<String>,
+    description: Option<String>,
+    #[serde(default)]
+    subscribers: i64,
+    #[serde(default)]
+    users_active_day: i64,
+    #[serde(default)]
+    users_active_week: i64,
+}
+
+impl From<Community> for types::Community {
+    fn from(community: Community) -> Self {
+        Self {
+            id: community.id,
+            host: host(&community.ap_id),
+            name: community.name,
+            title: community.title,
+            icon: community.icon,
+            nsfw: community.nsfw,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Post {
+    id: PostId,
+    name: String,
+    url: Option<String>,
+    body: Option<String>,
+    embed_description: Option<String>,
+    thumbnail_url: Option<String>,
+    #[serde(default)]
+    nsfw: bool,
+    #[serde(default)]
+    featured_local: bool,
+    #[serde(default)]
+    featured_community: bool,
+    #[serde(default)]
+    score: i64,
+    #[serde(default)]
+    upvotes: i64,
+    #[serde(default)]
+    downvotes: i64,
+    #[serde(default)]
+    comments: i64,
+}
+
+/// Timestamps replaced most flags, a set one means `true`.
+#[derive(Deserialize)]
+struct PostActions {
+    read_at: Option<IgnoredAny>,
+    saved_at: Option<IgnoredAny>,
+    vote_is_upvote: Option<bool>,
+    /// Sent instead of `vote_is_upvote` before 1.0 was released.
+    like_score: Option<i16>,
+    read_comments_amount: Option<i64>,
+}
+
+fn my_vote(vote_is_upvote: Option<bool>, like_score: Option<i16>) -> Option<i16> {
+    match vote_is_upvote {
+        Some(true) => Some(1),
+        Some(false) => Some(-1),
+        None => like_score,
+    }
+}
+
+#[derive(Deserialize)]
+pub(super) struct PostView {
+    post: Post,
+    creator: Person,
+    community: Community,
+    post_actions: Option<PostActions>,
+}
+
+impl From<PostView> for types::Post {
+    fn from(view: PostView) -> Self {
+        let post = view.post;
+        let actions = view.post_actions;
+        let actions = actions.as_ref();
+        let read_comments = actions.and_then(|actions| actions.read_comments_amount);
+
+        Self {
+            id: post.id,
+            name: post.name,
+            body: post.body,
+            embed_description: post.embed_description,
+            url: post.url,
+            thumbnail_url: post.thumbnail_url,
+            nsfw: post.nsfw,
+            featured_local: post.featured_local,
+            featured_community: post.featured_community,
+            creator: view.creator.into(),
+            community: view.community.into(),
+            score: post.score,
+            upvotes: post.upvotes,
+            downvotes: post.downvotes,
+            comments: post.comments,
+            my_vote: actions
+                .and_then(|actions| my_vote(actions.vote_is_upvote, actions.like_score)),
+            saved: actions.is_some_and(|actions| actions.saved_at.is_some()),
+            read: actions.is_some_and(|actions| actions.read_at.is_some()),
+            unread_comments: post.comments - read_comments.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<Paged<PostView>> for PostsPage {
+    fn from(page: Paged<PostView>) -> Self {
+        Self {
+            posts: page.items.into_iter().map(types::Post::from).collect(),
+            next_page: page.next_page,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(super) struct GetPostResponse {
+    pub(super) post_view: PostView,
+}
+
+#[derive(Deserialize)]
+struct Comment {
+    id: CommentId,
+    post_id: PostId,
+    content: String,
+    path: String,
+}
+
+/// The post a comment was left under, of which only the title is shown.
+#[derive(Deserialize)]
+struct CommentPost {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct CommentActions {
+    saved_at: Option<IgnoredAny>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct CommentView {
+    comment: Comment,
+    creator: Person,
+    post: CommentPost,
+    community: Community,
+    comment_actions: Option<CommentActions>,
+}
+
+impl From<CommentView> for types::Comment {
+    fn from(view: CommentView) -> Self {
+        Self {
+            id: view.comment.id,
+            content: view.comment.content,
+            path: view.comment.path,
+            post_id: view.comment.post_id,
+            post_name: view.post.name,
+            creator: view.creator.into(),
+            community: view.community.into(),
+            saved: view
+                .comment_actions
+                .is_some_and(|actions| actions.saved_at.is_some()),
+        }
+    }
+}
+
+/// What `person/content` and `account/saved` list, both in one list.
+#[derive(Deserialize)]
+#[serde(tag = "type_")]
+pub(super) enum Content {
+    Post(PostView),
+    Comment(CommentView),
+}
+
+impl From<Paged<Content>> for PostsPage {
+    fn from(page: Paged<Content>) -> Self {
+        let posts = page
+            .items
+            .into_iter()
+            .filter_map(|content| match content {
+                Content::Post(view) => Some(view.into()),
+                Content::Comment(_) => None,
+            })
+            .collect();
+        Self {
+            posts,
+            next_page: page.next_page,
+        }
+    }
+}
+
+/// Splits the content of `person/content` or `account/saved` into posts and comments.
+fn split_content(content: Vec<Content>) -> (Vec<types::Post>, Vec<types::Comment>) {
+    let (mut posts, mut comments) = (vec![], vec![]);
+    for content in content {
+        match content {
+            Content::Post(view) => posts.push(view.into()),
+            Content::Comment(view) => comments.push(view.into()),
+        }
+    }
+    (posts, comments)
+}
+
+pub(super) fn comments(page: Paged<Content>) -> Vec<types::Comment> {
+    split_content(page.items).1
+}
+
+#[derive(Deserialize)]
+struct CommunityActions {
+    follow_state: Option<String>,
+    blocked_at: Option<IgnoredAny>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct CommunityView {
+    community: Community,
+    community_actions: Option<CommunityActions>,
+}
+
+impl From<CommunityView> for CommunityInfo {
+    fn from(view: CommunityView) -> Self {
+        let actions = view.community_actions;
+        let subscribed = match actions
+            .as_ref()
+            .and_then(|actions| actions.follow_state.as_deref())
+        {
+            Some("Accepted") => SubscribedType::Subscribed,
+            Some("Pending" | "ApprovalRequired") => SubscribedType::Pending,
+            _ => SubscribedType::NotSubscribed,
+        };
+        let community = view.community;
+
+        Self {
+            description: community.sidebar.clone().or(community.description.clone()),
+            subscribers: community.subscribers,
+            users_active_day: community.users_active_day,
+            users_active_week: community.users_active_week,
+            subscribed,
+            blocked: actions.is_some_and(|actions| actions.blocked_at.is_some()),
+            community: community.into(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ModeratorView {
+    community: Community,
+    moderator: Person,
+}
+
+#[derive(Deserialize)]
+pub(super) struct GetCommunityResponse {
+    community_view: CommunityView,
+    #[serde(default)]
+    moderators: Vec<ModeratorView>,
+}
+
+impl From<GetCommunityResponse> for CommunityDetails {
+    fn from(res: GetCommunityResponse) -> Self {
+        Self {
+            info: res.community_view.into(),
+            moderators: res
+                .moderators
+                .into_iter()
+                .map(|view| view.moderator.into())
+                .collect(),
+        }
+    }
+}
+
+/// What following or blocking a community answers with.
+#[derive(Deserialize)]
+pub(super) struct CommunityResponse {
+    community_view: CommunityView,
+    /// Only sent for blocks, which the view tells as well.
+    blocked: Option<bool>,
+}
+
+impl From<CommunityResponse> for CommunityInfo {
+    fn from(res: CommunityResponse) -> Self {
+        let mut info = CommunityInfo::from(res.community_view);
+        if let Some(blocked) = res.blocked {
+            info.blocked = blocked;
+        }
+        info
+    }
+}
+
+#[derive(Deserialize)]
+pub(super) struct BlockPersonResponse {
+    pub(super) blocked: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct PersonView {
+    person: Person,
+    #[serde(default)]
+    is_admin: bool,
+    #[serde(default, alias = "creator_banned")]
+    banned: bool,
+}
+
+impl From<PersonView> for PersonInfo {
+    fn from(view: PersonView) -> Self {
+        let person = view.person;
+        Self {
+            banner: person.banner.clone(),
+            bio: person.bio.clone(),
+            published: person.published_at,
+            bot_account: person.bot_account,
+            banned: view.banned,
+            is_admin: view.is_admin,
+            post_count: person.post_count,
+            comment_count: person.comment_count,
+            person: person.into(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub(super) struct GetPersonResponse {
+    person_view: PersonView,
+    #[serde(default)]
+    moderates: Vec<ModeratorView>,
+}
+
+/// The person, along with their content from `person/content` or `account/saved`.
+pub(super) fn person_details(res: GetPersonResponse, content: Paged<Content>) -> PersonDetails {
+    let (posts, comments) = split_content(content.items);
+    PersonDetails {
+        info: res.person_view.into(),
+        posts,
+        comments,
+        moderates: res
+            .moderates
+            .into_iter()
+            .map(|view| view.community.into())
+            .collect(),
+    }
+}
+
+/// Results come as one list of tagged entries instead of a list per type.
+#[derive(Deserialize)]
+#[serde(tag = "type_")]
+enum SearchResult {
+    Post(PostView),
+    Comment(CommentView),
+    Community(CommunityView),
+    Person(PersonView),
+    /// Like the multi-communities 0.19 doesn't know.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Deserialize)]
+pub(super) struct SearchResponse {
+    #[serde(alias = "items")]
+    search: Vec<SearchResult>,
+}
+
+impl From<SearchResponse> for SearchResults {
+    fn from(res: SearchResponse) -> Self {
+        let mut results = SearchResults::default();
+        for result in res.search {
+            match result {
+                SearchResult::Post(view) => results.posts.push(view.into()),
+                SearchResult::Comment(view) => results.comments.push(view.into()),
+                SearchResult::Community(view) => results.communities.push(view.into()),
+                SearchResult::Person(view) => results.users.push(view.into()),
+                SearchResult::Other => (),
+            }
+        }
+        results
+    }
+}
+
+#[derive(Deserialize)]
+struct Site {
+    name: String,
+    icon: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LocalSite {
+    default_post_listing_type: Option<String>,
+    default_post_sort_type: Option<String>,
+    default_post_time_range_seconds: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct SiteView {
+    site: Site,
+    local_site: LocalSite,
+}
+
+/// `GET site`, which no longer tells anything about the account.
+#[derive(Deserialize)]
+pub(super) struct GetSiteResponse {
+    site_view: SiteView,
+}
+
+#[derive(Deserialize)]
+struct LocalUser {
+    default_listing_type: Option<String>,
+    default_post_sort_type: Option<String>,
+    default_post_time_range_seconds: Option<u64>,
+    #[serde(default)]
+    show_nsfw: bool,
+    #[serde(default = "shown")]
+    show_read_posts: bool,
+    #[serde(default)]
+    admin: bool,
+}
+
+fn shown() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct Id<T> {
+    id: T,
+}
+
+#[derive(Deserialize)]
+struct LocalUserView {
+    local_user: LocalUser,
+    person: Id<PersonId>,
+}
+
+#[derive(Deserialize)]
+struct Follow {
+    community: Id<CommunityId>,
+}
+
+/// `GET account`, what 0.19 sent along with the site as `my_user`. Blocks are
+/// plain lists of what's blocked.
+#[derive(Deserialize)]
+pub(super) struct MyUserInfo {
+    local_user_view: LocalUserView,
+    #[serde(default)]
+    follows: Vec<Follow>,
+    #[serde(default)]
+    community_blocks: Vec<Id<CommunityId>>,
+    #[serde(default)]
+    person_blocks: Vec<Id<PersonId>>,
+    #[serde(default, alias = "instance_communities_blocks")]
+    instance_blocks: Vec<Id<InstanceId>>,
+}
+
+pub(super) fn site_info(res: GetSiteResponse, my_user: Option<MyUserInfo>) -> SiteInfo {
+    let site = res.site_view.site;
+    let local_site = res.site_view.local_site;
+
+    let mut info = SiteInfo {
+        name: site.name,
+        icon: site.icon,
+        default_listing_type: listing_type_from_v4(local_site.default_post_listing_type.as_deref()),
+        default_sort_type: sort_from_v4(
+            local_site.default_post_sort_type.as_deref(),
+            local_site.default_post_time_range_seconds,
+        ),
+        ..SiteInfo::default()
+    };
+
+    if let Some(my_user) = my_user {
+        let local_user = my_user.local_user_view.local_user;
+        info.default_listing_type =
+            listing_type_from_v4(local_user.default_listing_type.as_deref());
+        info.default_sort_type = sort_from_v4(
+            local_user.default_post_sort_type.as_deref(),
+            local_user.default_post_time_range_seconds,
+        );
+        info.show_nsfw = local_user.show_nsfw;
+        info.show_read_posts = local_user.show_read_posts;
+        info.is_admin = local_user.admin;
+        info.person_id = Some(my_user.local_user_view.person.id);
+
+        info.subscriptions = my_user
+            .follows
+            .into_iter()
+            .map(|follow| follow.community.id)
+            .collect();
+        info.blocked_communities = my_user
+            .community_blocks
+            .into_iter()
+            .map(|community| community.id)
+            .collect();
+        info.blocked_people = my_user
+            .person_blocks
+            .into_iter()
+            .map(|person| person.id)
+            .collect();
+        info.blocked_instances = my_user
+            .instance_blocks
+            .into_iter()
+            .map(|instance| instance.id)
+            .collect();
+    }
+
+    info
+}
+
+/// Replies, mentions and private messages are counted together now.
+#[derive(Deserialize)]
+pub(super) struct UnreadCount {
+    #[serde(alias = "notification_count")]
+    pub(super) count: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::de::DeserializeOwned;
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    /// A response as a Lemmy 1.0 instance sent it.
+    fn fixture<T: DeserializeOwned>(name: &str) -> T {
+        let path = format!(
+            "{}/src/client/fixtures/v4/{name}.json",
+            env!("CARGO_MANIFEST_DIR")
+        );
+        let json = std::fs::read_to_string(&path).unwrap();
+        serde_json::from_str(&json).unwrap_or_else(|e| panic!("{name}.json: {e}"))
+    }
+
+    fn query<T: Serialize>(params: &T) -> Value {
+        serde_json::to_value(params).unwrap()
+    }
+
+    #[test]
+    fn rewrites_top_sorts_to_time_ranges() {
+        let req = GetPosts {
+            sort: Some(SortType::TopWeek),
+            limit: Some(20),
+            ..Default::default()
+        };
+        assert_eq!(
+            query(&ListPosts::from(&req)),
+            json!({
+                "type_": null, "sort": "Top", "time_range_seconds": 604_800,
+                "community_id": null, "show_read": null, "page_cursor": null, "limit": 20,
+            })
+        );
+
+        for (sort, v4) in [
+            (SortType::TopAll, ("Top", None)),
+            (SortType::Hot, ("Hot", None)),
+            (SortType::TopHour, ("Top", Some(3_600))),
+        ] {
+            assert_eq!(post_sort(sort), (v4.0.to_string(), v4.1));
+        }
+    }
+
+    #[test]
+    fn maps_sorts_back() {
+        assert_eq!(sort_from_v4(Some("Hot"), None), SortType::Hot);
+        assert_eq!(sort_from_v4(Some("Top"), Some(86_400)), SortType::TopDay);
+        assert_eq!(sort_from_v4(Some("Top"), Some(1)), SortType::TopAll);
+        assert_eq!(sort_from_v4(Some("Top"), None), SortType::TopAll);
+        assert_eq!(sort_from_v4(Some("Unknown"), None), SortType::Hot);
+        assert_eq!(sort_from_v4(None, None), SortType::Hot);
+    }
+
+    #[test]
+    fn rewrites_community_sorts() {
+        for (sort, v4) in [
+            (SortType::New, "New"),
+            (SortType::Active, "ActiveWeekly"),
+            (SortType::MostComments, "Comments"),
+            (SortType::TopMonth, "Subscribers"),
+            (SortType::Controversial, "Hot"),
+        ] {
+            assert_eq!(community_sort(sort), v4);
+        }
+    }
+
+    #[test]
+    fn rewrites_searches() {
+        let req = Search {
+            q: "rust".into(),
+            type_: Some(SearchType::Url),
+            sort: Some(SortType::Active),
+            ..Default::default()
+        };
+        let params = query(&SearchV4::from(&req));
+        assert_eq!(params["search_term"], "rust");
+        assert_eq!(params["type_"], "Posts");
+        assert_eq!(params["post_url_only"], true);
+        assert_eq!(params["sort"], "Top");
+        assert_eq!(params["time_range_seconds"], Value::Null);
+
+        let req = Search {
+            q: "rust".into(),
+            sort: Some(SortType::TopDay),
+            ..Default::default()
+        };
+        let params = query(&SearchV4::from(&req));
+        assert_eq!(params["sort"], "Top");
+        assert_eq!(params["time_range_seconds"], 86_400);
+        assert_eq!(params["post_url_only"], Value::Null);
+    }
+
+    #[test]
+    fn rewrites_votes() {
+        for (score, is_upvote) in [(1, json!(true)), (-1, json!(false)), (0, Value::Null)] {
+            let req = CreatePostLike {
+                post_id: PostId(3),
+                score,
+            };
+            assert_eq!(
+                query(&LikePost::from(&req)),
+                json!({ "post_id": 3, "is_upvote": is_upvote })
+            );
+        }
+    }
+
+    #[test]
+    fn marks_a_single_post_as_read() {
+        let req = MarkPostAsRead {
+            post_ids: vec![PostId(3)],
+            read: true,
+        };
+        assert_eq!(
+            query(&MarkPostRead::from(&req)),
+            json!({ "post_id": 3, "read": true })
+        );
+    }
+
+    #[test]
+    fn decodes_posts() {
+        let page: PostsPage = fixture::<Paged<PostView>>("post_list").into();
+
+        assert_eq!(page.posts.len(), 2);
+        assert!(page.next_page.is_some());
+
+        let post = &page.posts[0];
+        assert_eq!(post.name, "Lemmy 1.0 released");
+        assert_eq!(
+            post.url.as_deref(),
+            Some("https://join-lemmy.org/news/2025-10-01_-_Lemmy_Release_v1.0.0")
+        );
+        assert_eq!(post.creator.name, "dessalines");
+        assert_eq!(post.creator.host, "lemmy.ml");
+        assert_eq!(post.community.name, "announcements");
+        assert_eq!((post.score, post.upvotes, post.downvotes), (812, 830, 18));
+        assert_eq!(post.comments, 154);
+        assert_eq!(post.my_vote, Some(1));
+        assert!(post.saved);
+        assert!(post.read);
+        assert_eq!(post.unread_comments, 4);
+        assert!(post.featured_local);
+
+        // Nothing done to it yet, so it comes without any actions.
+        let post = &page.posts[1];
+        assert_eq!(post.my_vote, None);
+        assert!(!post.saved && !post.read);
+        assert!(post.nsfw);
+        assert_eq!(post.thumbnail_url, None);
+    }
+
+    #[test]
+    fn decodes_a_single_post() {
+        let res: GetPostResponse = fixture("post");
+        let post = types::Post::from(res.post_view);
+
+        assert_eq!(post.id, PostId(31_340));
+        assert_eq!(post.my_vote, Some(-1));
+        assert!(post.body.is_some());
+    }
+
+    #[test]
+    fn decodes_comments() {
+        let comments: Vec<types::Comment> = fixture::<Paged<CommentView>>("comment_list").items();
+
+        let comment = &comments[0];
+        assert_eq!(comment.content, "Congrats to everyone involved!");
+        assert_eq!(comment.path, "0.4021");
+        assert_eq!(comment.post_id, PostId(31_337));
+        assert_eq!(comment.post_name, "Lemmy 1.0 released");
+        assert_eq!(comment.creator.host, "programming.dev");
+        assert!(comment.saved);
+
+        let reply = &comments[1];
+        assert_eq!(reply.path, "0.4021.4022");
+        assert!(!reply.saved);
+    }
+
+    #[test]
+    fn decodes_communities() {
+        let details: CommunityDetails = fixture::<GetCommunityResponse>("community").into();
+
+        let info = &details.info;
+        assert_eq!(info.community.title, "Rust Programming");
+        assert_eq!(info.community.host, "programming.dev");
+        assert_eq!(info.subscribers, 41_240);
+        assert_eq!(info.users_active_week, 1_510);
+        assert_eq!(info.subscribed, SubscribedType::Subscribed);
+        assert!(!info.blocked);
+        assert!(info.description.as_deref().unwrap().starts_with("Welcome"));
+        assert_eq!(details.moderators[0].name, "snowe");
+
+        let communities: Vec<CommunityInfo> =
+            fixture::<Paged<CommunityView>>("community_list").items();
+        assert_eq!(communities.len(), 2);
+        assert_eq!(communities[1].subscribed, SubscribedType::Pending);
+        assert!(communities[1].blocked);
+    }
+
+    #[test]
+    fn decodes_blocks() {
+        let info: CommunityInfo = fixture::<CommunityResponse>("block_community").into();
+        assert!(info.blocked);
+
+        let res: BlockPersonResponse = fixture("block_person");
+        assert_eq!(res.blocked, Some(true));
+    }
+
+    #[test]
+    fn decodes_people() {
+        let details = person_details(fixture("person"), fixture("person_content"));
+
+        let info = &details.info;
+        assert_eq!(info.person.name, "nutomic");
+        assert_eq!(info.person.display_name, None);
+        assert_eq!(info.published.timestamp(), 1_550_000_000);
+        assert_eq!((info.post_count, info.comment_count), (321, 4_567));
+        assert!(info.is_admin);
+        assert!(!info.banned);
+        assert_eq!(details.posts.len(), 1);
+        assert_eq!(details.comments.len(), 1);
+        assert_eq!(details.moderates[0].name, "announcements");
+    }
+
+    #[test]
+    fn decodes_saved_content() {
+        let page: PostsPage = fixture::<Paged<Content>>("saved").into();
+        assert_eq!(page.posts.len(), 1);
+        assert!(page.posts[0].saved);
+
+        let saved_comments = comments(fixture("saved"));
+        assert_eq!(saved_comments.len(), 1);
+        assert!(saved_comments[0].saved);
+    }
+
+    #[test]
+    fn decodes_search_results() {
+        let results: SearchResults = fixture::<SearchResponse>("search").into();
+
+        assert_eq!(results.posts.len(), 1);
+        assert_eq!(results.comments.len(), 1);
+        assert_eq!(results.communities.len(), 1);
+        assert_eq!(results.users[0].person.name, "nutomic");
+    }
+
+    #[test]
+    fn decodes_the_site_and_account() {
+        let info = site_info(fixture("site"), None);
+        assert_eq!(info.name, "Lemmy");
+        assert_eq!(
+            info.icon.as_deref(),
+            Some("https://lemmy.ml/pictrs/image/0d9f5d64-7c59-4a2e-9b43-1f8b1b3c2a43.png")
+        );
+        assert_eq!(info.default_listing_type, ListingType::Local);
+        assert_eq!(info.default_sort_type, SortType::Active);
+        assert_eq!(info.person_id, None);
+
+        let info = site_info(fixture("site"), Some(fixture("account")));
+        assert_eq!(info.default_listing_type, ListingType::Subscribed);
+        assert_eq!(info.default_sort_type, SortType::TopWeek);
+        assert!(info.show_nsfw);
+        assert!(!info.show_read_posts);
+        assert!(!info.is_admin);
+        assert_eq!(info.person_id, Some(PersonId(8_421)));
+        assert!(info.subscriptions.contains(&CommunityId(15)));
+        assert!(info.blocked_communities.contains(&CommunityId(99)));
+        assert!(info.blocked_people.contains(&PersonId(77)));
+        assert!(info.blocked_instances.contains(&InstanceId(42)));
+    }
+
+    #[test]
+    fn decodes_the_unread_count() {
+        let res: UnreadCount = fixture("unread_count");
+        assert_eq!(res.count, 3);
+    }
+}
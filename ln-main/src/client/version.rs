@@ -0,0 +1,170 @@
+use std::fmt::Display;
+
+use anyhow::{bail, Result};
+use serde::Deserialize;
+use tracing::{info, warn};
+
+use super::{ClientError, ClientResult, LemmyClient};
+
+/// The HTTP API generation spoken by the instance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApiVersion {
+    /// Lemmy 0.19, whose request types Lemmynator builds its requests with.
+    V3,
+    /// Lemmy 1.0, whose requests and responses are translated by [`super::v4`].
+    V4,
+}
+
+impl ApiVersion {
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ApiVersion::V3 => "v3",
+            ApiVersion::V4 => "v4",
+        }
+    }
+
+    fn for_server(version: &ServerVersion) -> Result<Self> {
+        match (version.major, version.minor) {
+            (0, 19) => Ok(ApiVersion::V3),
+            (1, _) => Ok(ApiVersion::V4),
+            (0, _) => bail!(
+                "the instance runs Lemmy {version}, which is too old. Lemmynator needs at least 0.19"
+            ),
+            _ => bail!(
+                "the instance runs Lemmy {version}, which is newer than Lemmynator supports (0.19 and 1.x)"
+            ),
+        }
+    }
+}
+
+pub struct ServerVersion {
+    major: u64,
+    minor: u64,
+    raw: String,
+}
+
+impl ServerVersion {
+    fn parse(raw: &str) -> Option<Self> {
+        let mut parts = raw.split(['.', '-', '+']);
+        Some(Self {
+            major: parts.next()?.parse().ok()?,
+            minor: parts.next()?.parse().ok()?,
+            raw: raw.to_string(),
+        })
+    }
+}
+
+impl Display for ServerVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.raw)
+    }
+}
+
+#[derive(Deserialize)]
+struct NodeInfoLinks {
+    links: Vec<NodeInfoLink>,
+}
+
+#[derive(Deserialize)]
+struct NodeInfoLink {
+    href: String,
+}
+
+#[derive(Deserialize)]
+struct NodeInfo {
+    software: Software,
+}
+
+#[derive(Deserialize)]
+struct Software {
+    name: String,
+    version: String,
+}
+
+impl LemmyClient {
+    /// Asks the instance which Lemmy it runs and picks the API to talk to it with.
+    ///
+    /// Only an instance that can't be reached at all is assumed to run 0.19,
+    /// so that cached content can still be browsed while offline. One that
+    /// answers without telling its version is refused, as guessing wrong would
+    /// only show up later as puzzling errors.
+    pub async fn negotiate_version(&self) -> Result<()> {
+        let software = match self.software().await {
+            Ok(software) => software,
+            Err(e @ (ClientError::Timeout | ClientError::Network(_))) => {
+                warn!("Could not reach the instance ({e}), assuming Lemmy 0.19");
+                return Ok(());
+            }
+            Err(e) => bail!("the instance runs an unknown server version ({e})"),
+        };
+
+        if software.name != "lemmy" {
+            bail!("the instance runs {}, not Lemmy", software.name);
+        }
+
+        let Some(version) = ServerVersion::parse(&software.version) else {
+            bail!(
+                "the instance runs Lemmy {}, an unknown version Lemmynator doesn't support",
+                software.version
+            );
+        };
+
+        let api_version = ApiVersion::for_server(&version)?;
+        info!("Instance runs Lemmy {version}, using API {api_version:?}");
+        *self.api_version.write().unwrap() = api_version;
+        Ok(())
+    }
+
+    async fn software(&self) -> ClientResult<Software> {
+        let links: NodeInfoLinks = self
+            .get_json(&format!("{}/.well-known/nodeinfo", self.base_url))
+            .await?;
+
+        // The last link points to the newest schema the instance supports.
+        let href = links
+            .links
+            .last()
+            .map(|link| link.href.clone())
+            .ok_or_else(|| ClientError::Decode("nodeinfo without any links".into()))?;
+
+        let node_info: NodeInfo = self.get_json(&href).await?;
+        Ok(node_info.software)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn api_version(raw: &str) -> Result<ApiVersion> {
+        ApiVersion::for_server(&ServerVersion::parse(raw).unwrap())
+    }
+
+    #[test]
+    fn parses_versions() {
+        for (raw, major, minor) in [
+            ("0.19.7", 0, 19),
+            ("1.0.0-alpha.4", 1, 0),
+            ("0.19.3+build", 0, 19),
+        ] {
+            let version = ServerVersion::parse(raw).unwrap();
+            assert_eq!((version.major, version.minor), (major, minor));
+            assert_eq!(version.to_string(), raw);
+        }
+        assert!(ServerVersion::parse("unknown").is_none());
+        assert!(ServerVersion::parse("1").is_none());
+    }
+
+    #[test]
+    fn picks_the_api() {
+        assert_eq!(api_version("0.19.7").unwrap(), ApiVersion::V3);
+        assert_eq!(api_version("1.0.0").unwrap(), ApiVersion::V4);
+        assert_eq!(api_version("1.2.0").unwrap(), ApiVersion::V4);
+    }
+
+    #[test]
+    fn refuses_unsupported_versions() {
+        assert!(api_version("0.18.5").is_err());
+        assert!(api_version("2.0.0").is_err());
+    }
+}
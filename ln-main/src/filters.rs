@@ -0,0 +1,233 @@
+//! Keeps posts matching the rules in `[filters]` out of listings.
+
+use anyhow::{Context, Result};
+use ln_config::Filters;
+use regex::Regex;
+
+use crate::types::Post;
+
+/// The rules from `[filters]`, ready to be matched against posts.
+pub struct PostFilters {
+    /// Lowercased, as they match regardless of case.
+    keywords: Vec<String>,
+    regexes: Vec<Regex>,
+    domains: Vec<String>,
+    users: Vec<String>,
+    communities: Vec<String>,
+}
+
+impl PostFilters {
+    pub fn new(filters: &Filters) -> Result<Self> {
+        let regexes = filters
+            .regexes
+            .iter()
+            .map(|regex| {
+                Regex::new(regex).with_context(|| format!("\"{regex}\" in [filters] is invalid"))
+            })
+            .collect::<Result<_>>()?;
+
+        let lowercase = |list: &[String]| list.iter().map(|item| item.to_lowercase()).collect();
+
+        Ok(Self {
+            keywords: lowercase(&filters.keywords),
+            regexes,
+            domains: lowercase(&filters.domains),
+            users: lowercase(&filters.users),
+            communities: lowercase(&filters.communities),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keywords.is_empty()
+            && self.regexes.is_empty()
+            && self.domains.is_empty()
+            && self.users.is_empty()
+            && self.communities.is_empty()
+    }
+
+    /// Whether any rule matches the post.
+    pub fn hides(&self, post: &Post) -> bool {
+        let body = post.body.as_deref().unwrap_or_default();
+        let url = post
+            .url
+            .as_deref()
+            .and_then(|url| url::Url::parse(url).ok());
+        let host = url.as_ref().and_then(|url| url.host_str());
+
+        self.mentions(&post.name, body)
+            || self.links_to(host)
+            || Self::is_listed(&self.users, &post.creator.name, Some(&post.creator.host))
+            || Self::is_listed(
+                &self.communities,
+                &post.community.name,
+                Some(&post.community.host),
+            )
+    }
+
+    /// Whether the title or the body has a keyword or matches a regex.
+    fn mentions(&self, title: &str, body: &str) -> bool {
+        let lowercase_title = title.to_lowercase();
+        let lowercase_body = body.to_lowercase();
+        let has_keyword = self.keywords.iter().any(|keyword| {
+            lowercase_title.contains(keyword.as_str()) || lowercase_body.contains(keyword.as_str())
+        });
+
+        has_keyword
+            || self
+                .regexes
+                .iter()
+                .any(|regex| regex.is_match(title) || regex.is_match(body))
+    }
+
+    /// Whether `host` is one of the domains or a subdomain of one.
+    fn links_to(&self, host: Option<&str>) -> bool {
+        let Some(host) = host.map(str::to_lowercase) else {
+            return false;
+        };
+
+        self.domains.iter().any(|domain| {
+            host == *domain
+                || host
+                    .strip_suffix(domain.as_str())
+                    .is_some_and(|subdomain| subdomain.ends_with('.'))
+        })
+    }
+
+    /// Names match on any instance, unless listed as `name@instance`.
+    fn is_listed(list: &[String], name: &str, host: Option<&str>) -> bool {
+        let name = name.to_lowercase();
+        let qualified = host.map(|host| format!("{name}@{host}"));
+        list.iter()
+            .any(|entry| *entry == name || Some(entry) == qualified.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    fn filters(filters: Filters) -> PostFilters {
+        PostFilters::new(&filters).unwrap()
+    }
+
+    #[test]
+    fn matches_keywords_regardless_of_case() {
+        let filters = filters(Filters {
+            keywords: list(&["Spoiler"]),
+            ..Default::default()
+        });
+
+        assert!(filters.mentions("SPOILERS ahead", ""));
+        assert!(filters.mentions("Episode 3", "contains a spoiler"));
+        assert!(!filters.mentions("Episode 3", "discussion"));
+        assert!(!filters.is_empty());
+    }
+
+    #[test]
+    fn matches_regexes() {
+        let filters = filters(Filters {
+            regexes: list(&[r"^\[(?i:meta)\]", r"\bv\d+\.\d+\b"]),
+            ..Default::default()
+        });
+
+        assert!(filters.mentions("[Meta] Rules update", ""));
+        assert!(filters.mentions("[META] Rules update", ""));
+        assert!(!filters.mentions("Rules update [meta]", ""));
+        assert!(filters.mentions("Release", "Now at v1.20"));
+        assert!(!filters.mentions("Release", "Now at version 1"));
+    }
+
+    #[test]
+    fn matches_domains_and_their_subdomains_only() {
+        let filters = filters(Filters {
+            domains: list(&["Example.com"]),
+            ..Default::default()
+        });
+
+        assert!(filters.links_to(Some("example.com")));
+        assert!(filters.links_to(Some("www.EXAMPLE.com")));
+        assert!(filters.links_to(Some("a.b.example.com")));
+        assert!(!filters.links_to(Some("notexample.com")));
+        assert!(!filters.links_to(Some("example.com.evil.org")));
+        assert!(!filters.links_to(None));
+    }
+
+    #[test]
+    fn matches_names_on_any_instance_unless_qualified() {
+        let users = list(&["spammer", "troll@lemmy.example"]);
+
+        assert!(PostFilters::is_listed(
+            &users,
+            "Spammer",
+            Some("lemmy.example")
+        ));
+        assert!(PostFilters::is_listed(
+            &users,
+            "spammer",
+            Some("other.example")
+        ));
+        assert!(PostFilters::is_listed(
+            &users,
+            "troll",
+            Some("lemmy.example")
+        ));
+        assert!(!PostFilters::is_listed(
+            &users,
+            "troll",
+            Some("other.example")
+        ));
+        assert!(!PostFilters::is_listed(&users, "troll", None));
+        assert!(!PostFilters::is_listed(
+            &users,
+            "someone",
+            Some("lemmy.example")
+        ));
+    }
+
+    #[test]
+    fn lowercases_listed_names() {
+        let filters = filters(Filters {
+            users: list(&["Spammer@Lemmy.Example"]),
+            communities: list(&["Memes"]),
+            ..Default::default()
+        });
+
+        assert!(PostFilters::is_listed(
+            &filters.users,
+            "spammer",
+            Some("lemmy.example")
+        ));
+        assert!(PostFilters::is_listed(
+            &filters.communities,
+            "memes",
+            Some("lemmy.example")
+        ));
+    }
+
+    #[test]
+    fn nothing_matches_without_rules() {
+        let filters = filters(Filters::default());
+
+        assert!(filters.is_empty());
+        assert!(!filters.mentions("Anything", "at all"));
+        assert!(!filters.links_to(Some("example.com")));
+    }
+
+    #[test]
+    fn rejects_invalid_regexes() {
+        let Err(e) = PostFilters::new(&Filters {
+            regexes: list(&["fine", "(unclosed"]),
+            ..Default::default()
+        }) else {
+            panic!("an invalid regex was accepted");
+        };
+
+        assert!(e
+            .to_string()
+            .contains("\"(unclosed\" in [filters] is invalid"));
+    }
+}
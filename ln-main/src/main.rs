@@ -1,12 +1,18 @@
 mod action;
 mod app;
 mod app_key_event;
+mod cache;
+mod cli;
+mod client;
+mod filters;
 mod tui;
 pub mod types;
 mod ui;
+mod unreads;
 
 use anyhow::Result;
 use app::App;
+use cli::Args;
 use tracing::info;
 
 #[tokio::main(flavor = "multi_thread")]
@@ -14,9 +20,11 @@ async fn main() -> Result<()> {
     let log_file = std::fs::File::create("/tmp/lemmynator.log").unwrap();
     tracing_subscriber::fmt().with_writer(log_file).init();
 
+    let args = Args::parse()?;
+
     info!("Lemmynator is starting");
 
-    let mut app = App::new().await?;
+    let mut app = App::new(args).await?;
     app.run().await?;
 
     info!("Lemmynator is quitting");
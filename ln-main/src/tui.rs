@@ -70,10 +70,8 @@ impl Tui {
         event_tx: &UnboundedSender<Event>,
     ) {
         match event {
-            Some(Ok(CrosstermEvent::Key(key))) => {
-                if key.kind == KeyEventKind::Press {
-                    event_tx.send(Event::Key(key)).unwrap();
-                }
+            Some(Ok(CrosstermEvent::Key(key))) if key.kind == KeyEventKind::Press => {
+                event_tx.send(Event::Key(key)).unwrap();
             }
             Some(Ok(CrosstermEvent::Resize(_, _))) => {
                 *PICKER.write().unwrap() = Picker::from_query_stdio().unwrap();
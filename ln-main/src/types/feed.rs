@@ -0,0 +1,31 @@
+use std::fmt::Display;
+
+use lemmy_api_common::lemmy_db_schema::{newtypes::CommunityId, ListingType};
+
+/// Where the posts of a listing come from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Feed {
+    /// One of the tabs.
+    Listing(ListingType),
+    Community(CommunityId),
+    /// The posts the account saved.
+    Saved,
+}
+
+impl Display for Feed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Feed::Listing(listing_type) => write!(f, "{listing_type}"),
+            Feed::Community(_) => write!(f, "community"),
+            Feed::Saved => write!(f, "saved"),
+        }
+    }
+}
+
+/// A community as the user asked for it, either picked from a post or typed in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommunityRef {
+    Id(CommunityId),
+    /// Like `rust` or `rust@programming.dev`.
+    Name(String),
+}
@@ -0,0 +1,133 @@
+//! What Lemmynator reads from an instance, whichever version of the API it
+//! speaks. The client turns every response into these right away, so nothing
+//! past it knows how Lemmy shapes its JSON.
+
+use chrono::{DateTime, Utc};
+use lemmy_api_common::{
+    lemmy_db_schema::{
+        newtypes::{CommentId, CommunityId, PersonId, PostId},
+        SubscribedType,
+    },
+    lemmy_db_views::structs::PaginationCursor,
+};
+use serde::{Deserialize, Serialize};
+
+/// Someone, as shown next to what they wrote.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Person {
+    pub id: PersonId,
+    pub name: String,
+    pub display_name: Option<String>,
+    /// The instance they're from.
+    pub host: String,
+    pub avatar: Option<String>,
+}
+
+/// A community, as shown next to what was posted in it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Community {
+    pub id: CommunityId,
+    pub name: String,
+    pub title: String,
+    /// The instance it's from.
+    pub host: String,
+    pub icon: Option<String>,
+    pub nsfw: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Post {
+    pub id: PostId,
+    pub name: String,
+    pub body: Option<String>,
+    /// What the linked page says about itself.
+    pub embed_description: Option<String>,
+    pub url: Option<String>,
+    pub thumbnail_url: Option<String>,
+    /// The post itself is marked NSFW, its community may be as well.
+    pub nsfw: bool,
+    pub featured_local: bool,
+    pub featured_community: bool,
+    pub creator: Person,
+    pub community: Community,
+    pub score: i64,
+    pub upvotes: i64,
+    pub downvotes: i64,
+    pub comments: i64,
+    pub my_vote: Option<i16>,
+    pub saved: bool,
+    pub read: bool,
+    /// Comments left since the post was last opened.
+    pub unread_comments: i64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Comment {
+    pub id: CommentId,
+    pub content: String,
+    /// The ids from the root down to the comment, like `0.12.34`.
+    pub path: String,
+    pub post_id: PostId,
+    /// The title of the post it was left under.
+    pub post_name: String,
+    pub creator: Person,
+    pub community: Community,
+    pub saved: bool,
+}
+
+/// A page of a listing.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PostsPage {
+    pub posts: Vec<Post>,
+    pub next_page: Option<PaginationCursor>,
+}
+
+/// A community along with how it's doing and how the account relates to it.
+#[derive(Clone, Debug)]
+pub struct CommunityInfo {
+    pub community: Community,
+    /// The sidebar, in markdown.
+    pub description: Option<String>,
+    pub subscribers: i64,
+    pub users_active_day: i64,
+    pub users_active_week: i64,
+    pub subscribed: SubscribedType,
+    pub blocked: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct CommunityDetails {
+    pub info: CommunityInfo,
+    pub moderators: Vec<Person>,
+}
+
+/// Someone along with what's on their profile.
+#[derive(Clone, Debug)]
+pub struct PersonInfo {
+    pub person: Person,
+    pub banner: Option<String>,
+    pub bio: Option<String>,
+    pub published: DateTime<Utc>,
+    pub bot_account: bool,
+    pub banned: bool,
+    pub is_admin: bool,
+    pub post_count: i64,
+    pub comment_count: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct PersonDetails {
+    pub info: PersonInfo,
+    /// Their latest posts and comments, or only what they saved when asked for that.
+    pub posts: Vec<Post>,
+    pub comments: Vec<Comment>,
+    pub moderates: Vec<Community>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct SearchResults {
+    pub posts: Vec<Post>,
+    pub comments: Vec<Comment>,
+    pub communities: Vec<CommunityInfo>,
+    pub users: Vec<PersonInfo>,
+}
@@ -5,10 +5,13 @@ use std::{
 };
 
 use image::DynamicImage;
-use lemmy_api_common::lemmy_db_views::structs::CommentView;
+use lemmy_api_common::{comment::SaveComment, lemmy_db_schema::newtypes::CommentId};
+use ln_config::CONFIG;
 use ratatui::{
     layout::{Margin, Offset},
     prelude::Rect,
+    style::{Style, Stylize},
+    text::{Line, Span},
     widgets::{Block, Paragraph, Wrap},
 };
 use ratatui_image::{
@@ -18,7 +21,9 @@ use ratatui_image::{
 use tracing::info;
 
 use crate::{
+    action::{Operation, UpdateAction},
     app::{self, PICKER},
+    types::{load_image, Comment},
     ui::components::Component,
 };
 
@@ -33,10 +38,16 @@ pub struct LemmynatorComment {
     pub content: String,
     pub author: Author,
     pub path: String,
+    pub saved: bool,
     pub replies: BTreeMap<i32, LemmynatorComment>,
 }
 
 impl LemmynatorComment {
+    pub fn toggle_save(&mut self, ctx: Arc<app::Ctx>) {
+        self.saved = !self.saved;
+        tokio::task::spawn(save_comment(ctx, CommentId(self.id), self.saved));
+    }
+
     fn depth(&self) -> u8 {
         u8::try_from(self.path.split('.').count() - 1).unwrap()
     }
@@ -74,31 +85,23 @@ pub enum CommentImage {
 
 static DEFAULT_USER_IMAGE: &[u8; 23864] = include_bytes!("../../imgs/user.png");
 
-impl From<CommentView> for LemmynatorComment {
-    fn from(value: CommentView) -> Self {
+impl LemmynatorComment {
+    pub fn from_lemmy_comment(value: Comment, ctx: Arc<app::Ctx>) -> Self {
         let avatar = if let Some(avatar_url) = value.creator.avatar {
             let image = Arc::new(Mutex::new(None));
-            let avatar_url = avatar_url.to_string();
 
             let avatar_url_clone = avatar_url.to_string();
             let image_clone = image.clone();
             tokio::task::spawn(async move {
-                let bytes = reqwest::get(avatar_url_clone)
-                    .await
-                    .unwrap()
-                    .bytes()
-                    .await
-                    .unwrap();
-
-                let image = match image::load_from_memory(&bytes) {
-                    Ok(image) => Ok(image),
-                    Err(e) => match e {
-                        image::ImageError::Unsupported(_) => return,
-                        _ => Err(e),
-                    },
-                }
-                .unwrap();
-                *image_clone.lock().unwrap() = Some(CommentImage::StatelessImage(image, false));
+                // The default avatar takes the place of one that can't be loaded.
+                let image = load_image(&ctx, &avatar_url_clone).await;
+                *image_clone.lock().unwrap() = Some(match image {
+                    Some(image) => CommentImage::StatelessImage(image, false),
+                    None => CommentImage::StatelessImage(
+                        image::load_from_memory(DEFAULT_USER_IMAGE).unwrap(),
+                        true,
+                    ),
+                });
             });
 
             AuthorAvatar {
@@ -121,30 +124,32 @@ impl From<CommentView> for LemmynatorComment {
         };
 
         LemmynatorComment {
-            content: value.comment.content,
+            content: value.content,
             author,
             replies: BTreeMap::new(),
-            id: value.comment.id.0,
-            path: value.comment.path,
+            id: value.id.0,
+            path: value.path,
+            saved: value.saved,
         }
     }
 }
 
-impl From<Vec<CommentView>> for LemmynatorPostComments {
-    fn from(value: Vec<CommentView>) -> Self {
+impl LemmynatorPostComments {
+    pub fn new(value: Vec<Comment>, ctx: Arc<app::Ctx>) -> Self {
         let mut comments = BTreeMap::new();
         let mut replies_to_a_comment = vec![];
 
-        for comment_view in value {
-            let comment_depth = comment_view.comment.path.split('.').count() - 1;
+        for comment in value {
+            let comment_depth = comment.path.split('.').count() - 1;
+
+            let lemmynator_comment =
+                LemmynatorComment::from_lemmy_comment(comment, Arc::clone(&ctx));
 
             if comment_depth != 1 {
-                replies_to_a_comment.push(comment_view.into());
+                replies_to_a_comment.push(lemmynator_comment);
                 continue;
             }
 
-            let lemmynator_comment: LemmynatorComment = comment_view.into();
-
             comments.insert(lemmynator_comment.id, lemmynator_comment);
         }
 
@@ -195,8 +200,21 @@ fn recursive_function(
     }
 }
 
+/// Saves or unsaves a comment, Lemmy keeps them for the account.
+pub async fn save_comment(ctx: Arc<app::Ctx>, comment_id: CommentId, save: bool) {
+    let req = SaveComment { comment_id, save };
+    if let Err(e) = ctx.api.save_comment(&req).await {
+        ctx.send_update_action(UpdateAction::Error(
+            Operation::SaveComment(comment_id, save),
+            e,
+        ));
+    }
+}
+
 pub struct LemmynatorPostCommentsWidget<'a> {
     left_side_width: u16,
+    /// The top level comment in focus, shown first.
+    focused: Option<usize>,
     comments: &'a mut BTreeMap<i32, LemmynatorComment>,
     ctx: Arc<app::Ctx>,
 }
@@ -205,6 +223,7 @@ impl<'a> LemmynatorPostCommentsWidget<'a> {
     pub fn new(ctx: Arc<app::Ctx>, comments: &'a mut BTreeMap<i32, LemmynatorComment>) -> Self {
         Self {
             left_side_width: 0,
+            focused: None,
             comments,
             ctx,
         }
@@ -214,14 +233,25 @@ impl<'a> LemmynatorPostCommentsWidget<'a> {
         Self {
             comments: self.comments,
             ctx: self.ctx,
+            focused: self.focused,
             left_side_width,
         }
     }
+
+    pub fn focused(self, focused: Option<usize>) -> Self {
+        Self {
+            comments: self.comments,
+            ctx: self.ctx,
+            left_side_width: self.left_side_width,
+            focused,
+        }
+    }
 }
 
 struct LemmynatorCommentWidget<'a> {
     comment: &'a LemmynatorComment,
     left_side_width: u16,
+    is_focused: bool,
 }
 
 impl<'a> LemmynatorCommentWidget<'a> {
@@ -229,13 +259,26 @@ impl<'a> LemmynatorCommentWidget<'a> {
         Self {
             comment,
             left_side_width,
+            is_focused: false,
         }
     }
+
+    fn focused(self, is_focused: bool) -> Self {
+        Self { is_focused, ..self }
+    }
 }
 
 impl<'a> Component for LemmynatorCommentWidget<'a> {
     fn render(&mut self, f: &mut ratatui::Frame, rect: Rect) {
-        let block = Block::bordered().title(self.comment.author.name.as_str());
+        let accent = Style::new().fg(CONFIG.general.accent_color);
+        let mut title = vec![Span::raw(self.comment.author.name.as_str())];
+        if self.comment.saved {
+            title.push(Span::styled(" ", accent));
+        }
+        let mut block = Block::bordered().title(Line::from(title));
+        if self.is_focused {
+            block = block.border_style(accent).title_style(Style::new().bold());
+        }
         f.render_widget(block, rect);
         f.render_widget(
             Paragraph::new(self.comment.content.as_str()).wrap(Wrap { trim: true }),
@@ -247,14 +290,14 @@ impl<'a> Component for LemmynatorCommentWidget<'a> {
 
         let mut avatar_image_lock = self.comment.author.avatar.image.lock().unwrap();
 
-        let new_image = avatar_image_lock.take().and_then(|image| match image {
-            CommentImage::StatelessImage(image, is_default) => Some(CommentImage::StatefulImage(
+        let new_image = avatar_image_lock.take().map(|image| match image {
+            CommentImage::StatelessImage(image, is_default) => CommentImage::StatefulImage(
                 (image.width(), image.height()),
                 PICKER.read().unwrap().new_resize_protocol(image),
                 is_default,
-            )),
+            ),
             CommentImage::StatefulImage(res, image, is_default) => {
-                Some(CommentImage::StatefulImage(res, image, is_default))
+                CommentImage::StatefulImage(res, image, is_default)
             }
         });
 
@@ -320,7 +363,8 @@ impl<'a> Component for LemmynatorPostCommentsWidget<'a> {
     fn render(&mut self, f: &mut ratatui::Frame, rect: Rect) {
         let mut lines_left: u16 = rect.height;
 
-        for (_, comment) in self.comments.iter_mut() {
+        let skipped = self.focused.unwrap_or_default();
+        for (idx, (_, comment)) in self.comments.iter_mut().enumerate().skip(skipped) {
             if lines_left <= 1 {
                 break;
             }
@@ -339,7 +383,9 @@ impl<'a> Component for LemmynatorPostCommentsWidget<'a> {
             }
             comment_rect.height = comment_height as u16;
 
-            LemmynatorCommentWidget::new(&comment, self.left_side_width).render(f, comment_rect);
+            LemmynatorCommentWidget::new(comment, self.left_side_width)
+                .focused(self.focused == Some(idx))
+                .render(f, comment_rect);
 
             if !comment.replies.is_empty() {
                 let comment_reply = comment.replies.iter().nth(0).unwrap().1;
@@ -370,7 +416,7 @@ impl<'a> Component for LemmynatorPostCommentsWidget<'a> {
 
                 info!("This is the reply rect: {:?}", reply_rect);
 
-                LemmynatorCommentWidget::new(&comment_reply, self.left_side_width)
+                LemmynatorCommentWidget::new(comment_reply, self.left_side_width)
                     .render(f, reply_rect);
             }
         }
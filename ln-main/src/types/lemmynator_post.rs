@@ -1,23 +1,24 @@
-use std::io::Cursor;
 use std::sync::{Arc, Mutex};
 
+use image::imageops::FilterType;
 use image::{DynamicImage, GenericImageView};
-use lemmy_api_common::lemmy_db_schema::newtypes::{CommunityId, PostId};
-use lemmy_api_common::lemmy_db_views::structs::PostView;
-use lemmy_api_common::post::CreatePostLike;
-use ln_config::CONFIG;
+use lemmy_api_common::lemmy_db_schema::newtypes::{CommunityId, PersonId, PostId};
+use lemmy_api_common::post::{CreatePostLike, MarkPostAsRead, SavePost};
+use ln_config::{NsfwMode, CONFIG};
 use ratatui::prelude::*;
 use ratatui::widgets::{Block, BorderType, Paragraph};
 use ratatui_image::protocol::StatefulProtocol;
 use ratatui_image::{Resize, StatefulImage};
 use ratskin::RatSkin;
 use text::ToSpan;
+use tracing::debug;
 
-use crate::action::Action;
+use crate::action::{Action, Operation, UpdateAction};
 use crate::app::{Ctx, PICKER};
 use crate::ui::components::Component;
 
 use crate::types::lemmynator_comment::LemmynatorPostComments;
+use crate::types::Post;
 
 #[derive(Clone)]
 pub struct LemmynatorPost {
@@ -28,10 +29,19 @@ pub struct LemmynatorPost {
     pub is_focused: bool,
     pub image_data: Arc<Mutex<Option<ThreadImage>>>,
     embed_url: Option<url::Url>,
+    thumbnail_url: Option<String>,
+    /// The post or its community is marked NSFW.
+    pub nsfw: bool,
+    thumbnail: Arc<Mutex<Thumbnail>>,
     pub author: String,
+    pub creator_id: PersonId,
     pub community: String,
     pub counts: LemmynatorCounts,
     pub my_vote: Option<i16>,
+    pub saved: bool,
+    pub read: bool,
+    /// Comments left since the post was last opened.
+    pub unread_comments: i64,
     is_featured_local: bool,
     is_featured_community: bool,
     pub comments: Option<LemmynatorPostComments>,
@@ -45,6 +55,15 @@ pub struct LemmynatorCounts {
     comments: i64,
 }
 
+/// How the thumbnail is shown, which NSFW posts only do as is once revealed.
+enum Thumbnail {
+    Revealed,
+    /// Shown pixelated once it's loaded, or not at all with [`NsfwMode::Hide`].
+    Obscured,
+    /// Shown pixelated, the image as downloaded is kept for when it's revealed.
+    Pixelated(DynamicImage),
+}
+
 pub struct ThreadImage {
     pub image: Arc<Mutex<StatefulProtocol>>,
     pub dimensions: (u32, u32),
@@ -61,6 +80,14 @@ impl ThreadImage {
         }
     }
 
+    /// Loads an image, like an icon or an avatar, into its slot.
+    pub async fn fetch(url: String, slot: Arc<Mutex<Option<ThreadImage>>>, ctx: Arc<Ctx>) {
+        if let Some(image) = load_image(&ctx, &url).await {
+            *slot.lock().unwrap() = Some(ThreadImage::new(image));
+            ctx.send_action(Action::Render);
+        }
+    }
+
     pub fn render(&mut self, f: &mut Frame, rect: Rect, ctx: Arc<Ctx>) {
         let needs_to_be_resized_to = self
             .image
@@ -83,57 +110,76 @@ impl ThreadImage {
 }
 
 impl LemmynatorPost {
-    pub fn from_lemmy_post(lemmy_post: PostView, ctx: Arc<Ctx>) -> Self {
+    pub fn from_lemmy_post(lemmy_post: Post, ctx: Arc<Ctx>) -> Self {
         let image = Arc::new(Mutex::new(None));
 
-        if let Some(ref url) = lemmy_post.post.thumbnail_url {
-            tokio::task::spawn(Self::fetch_image(
-                url.as_str().to_string(),
-                Arc::clone(&image),
-                Arc::clone(&ctx),
-            ));
+        let nsfw = lemmy_post.nsfw || lemmy_post.community.nsfw;
+        let nsfw_mode = if nsfw {
+            CONFIG.general.nsfw
+        } else {
+            NsfwMode::Show
+        };
+        let thumbnail_url = lemmy_post.thumbnail_url.clone();
+        let thumbnail = Arc::new(Mutex::new(match nsfw_mode {
+            NsfwMode::Show => Thumbnail::Revealed,
+            NsfwMode::Blur | NsfwMode::Hide => Thumbnail::Obscured,
+        }));
+
+        if let Some(url) = &thumbnail_url {
+            if nsfw_mode != NsfwMode::Hide {
+                tokio::task::spawn(Self::fetch_image(
+                    url.clone(),
+                    Arc::clone(&image),
+                    Arc::clone(&thumbnail),
+                    Arc::clone(&ctx),
+                ));
+            }
         }
 
         let embed_url = lemmy_post
-            .post
             .url
-            .as_ref()
-            .map(|db_url| url::Url::parse(db_url.as_str()))
-            .transpose()
-            .unwrap();
+            .as_deref()
+            .and_then(|url| url::Url::parse(url).ok());
 
         let body = Self::extract_body(&lemmy_post);
 
         let counts = LemmynatorCounts {
-            upvotes: lemmy_post.counts.upvotes,
-            downvotes: lemmy_post.counts.downvotes,
-            comments: lemmy_post.counts.comments,
+            upvotes: lemmy_post.upvotes,
+            downvotes: lemmy_post.downvotes,
+            comments: lemmy_post.comments,
         };
 
         LemmynatorPost {
-            id: lemmy_post.post.id,
-            name: lemmy_post.post.name,
+            id: lemmy_post.id,
+            name: lemmy_post.name,
             body,
             community: lemmy_post.community.name,
             community_id: lemmy_post.community.id,
             author: lemmy_post.creator.name,
+            creator_id: lemmy_post.creator.id,
             embed_url,
+            thumbnail_url,
+            nsfw,
+            thumbnail,
             is_focused: false,
             image_data: image,
             counts,
             my_vote: lemmy_post.my_vote,
-            is_featured_local: lemmy_post.post.featured_local,
-            is_featured_community: lemmy_post.post.featured_community,
+            saved: lemmy_post.saved,
+            read: lemmy_post.read,
+            unread_comments: lemmy_post.unread_comments,
+            is_featured_local: lemmy_post.featured_local,
+            is_featured_community: lemmy_post.featured_community,
             ctx,
             comments: None,
         }
     }
 
-    fn extract_body(lemmy_post: &PostView) -> String {
+    fn extract_body(lemmy_post: &Post) -> String {
         let unprocessed_body = {
-            if let Some(post_body) = &lemmy_post.post.body {
+            if let Some(post_body) = &lemmy_post.body {
                 post_body
-            } else if let Some(embed_desc) = &lemmy_post.post.embed_description {
+            } else if let Some(embed_desc) = &lemmy_post.embed_description {
                 embed_desc
             } else {
                 ""
@@ -156,30 +202,58 @@ impl LemmynatorPost {
         self.body.is_empty() && self.image_data.lock().unwrap().is_some()
     }
 
-    async fn fetch_image(url: String, image: Arc<Mutex<Option<ThreadImage>>>, ctx: Arc<Ctx>) {
-        let new_image = {
-            let res = ctx.client.get(url).send().await.unwrap();
-            Some(res.bytes().await.unwrap())
-        };
+    async fn fetch_image(
+        url: String,
+        image: Arc<Mutex<Option<ThreadImage>>>,
+        thumbnail: Arc<Mutex<Thumbnail>>,
+        ctx: Arc<Ctx>,
+    ) {
+        if let Some(dyn_image) = load_image(&ctx, &url).await {
+            // Held until the image is in place, so a reveal can't slip in between.
+            let mut thumbnail = thumbnail.lock().unwrap();
+            let dyn_image = match *thumbnail {
+                Thumbnail::Revealed => dyn_image,
+                Thumbnail::Obscured | Thumbnail::Pixelated(_) => {
+                    let pixelated = Self::pixelate(&dyn_image);
+                    *thumbnail = Thumbnail::Pixelated(dyn_image);
+                    pixelated
+                }
+            };
+            *image.lock().unwrap() = Some(ThreadImage::new(dyn_image));
+        }
+        ctx.send_action(Action::Render);
+    }
 
-        let new_image = if let Some(image) = new_image {
-            let dyn_image_res = image::ImageReader::new(Cursor::new(image))
-                .with_guessed_format()
-                .unwrap()
-                .decode();
-            if let Ok(dyn_image) = dyn_image_res {
-                Some(ThreadImage::new(dyn_image))
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+    /// Shrinks the image to a few pixels and scales it back up, so nothing can be made out.
+    fn pixelate(image: &DynamicImage) -> DynamicImage {
+        let (width, height) = image.dimensions();
+        image
+            .thumbnail(16, 16)
+            .resize_exact(width, height, FilterType::Nearest)
+    }
 
-        if let Some(new_image) = new_image {
-            *image.lock().unwrap() = Some(new_image);
+    /// Shows the thumbnail of an NSFW post as it is.
+    fn reveal(&mut self) {
+        let mut thumbnail = self.thumbnail.lock().unwrap();
+        match std::mem::replace(&mut *thumbnail, Thumbnail::Revealed) {
+            Thumbnail::Revealed => (),
+            Thumbnail::Pixelated(original) => {
+                *self.image_data.lock().unwrap() = Some(ThreadImage::new(original));
+                self.ctx.send_action(Action::Render);
+            }
+            // Still on its way when blurred, it then arrives as is.
+            Thumbnail::Obscured if CONFIG.general.nsfw == NsfwMode::Blur => (),
+            Thumbnail::Obscured => {
+                if let Some(url) = &self.thumbnail_url {
+                    tokio::task::spawn(Self::fetch_image(
+                        url.clone(),
+                        Arc::clone(&self.image_data),
+                        Arc::clone(&self.thumbnail),
+                        Arc::clone(&self.ctx),
+                    ));
+                }
+            }
         }
-        ctx.action_tx.send(Action::Render).unwrap();
     }
 
     fn vote(&mut self, mut new_score: i16) {
@@ -221,26 +295,34 @@ impl LemmynatorPost {
 
         tokio::task::spawn({
             let ctx = Arc::clone(&self.ctx);
-            let id = self.id.clone();
+            let id = self.id;
             async move {
                 let vote_req = CreatePostLike {
                     post_id: id,
                     score: new_score,
                 };
 
-                ctx.client
-                    .post(format!(
-                        "https://{}/api/v3/post/like",
-                        CONFIG.connection.instance
-                    ))
-                    .json(&vote_req)
-                    .send()
-                    .await
-                    .unwrap();
+                if let Err(e) = ctx.api.like_post(&vote_req).await {
+                    ctx.send_update_action(UpdateAction::Error(Operation::Vote(id, new_score), e));
+                }
             }
         });
     }
 
+    fn toggle_save(&mut self) {
+        self.saved = !self.saved;
+        tokio::task::spawn(save_post(Arc::clone(&self.ctx), self.id, self.saved));
+    }
+
+    /// Marks the post read, unless it already is or there's no account to keep track.
+    pub fn mark_read(&mut self) {
+        if self.read || self.ctx.is_anonymous() {
+            return;
+        }
+        self.read = true;
+        tokio::task::spawn(mark_post_read(Arc::clone(&self.ctx), self.id));
+    }
+
     pub fn desc_md_paragraph(&self, text_rect: Rect) -> Paragraph<'_> {
         let rat_skin = RatSkin::default();
         let text = RatSkin::parse_text(&self.body);
@@ -274,9 +356,44 @@ impl LemmynatorPost {
     }
 }
 
+/// Saves or unsaves a post, Lemmy keeps them for the account.
+/// Downloads and decodes an image: a thumbnail, an icon or an avatar.
+///
+/// Images only decorate what they're shown with, so one that can't be loaded
+/// is left out, or replaced by a placeholder, and merely logged. A dead image
+/// host would otherwise bring up a notification on every page.
+pub async fn load_image(ctx: &Ctx, url: &str) -> Option<DynamicImage> {
+    let result = match ctx.api.fetch_image(url).await {
+        Ok(bytes) => image::load_from_memory(&bytes).map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+
+    result
+        .inspect_err(|e| debug!("Could not load the image at {url}: {e}"))
+        .ok()
+}
+
+pub async fn save_post(ctx: Arc<Ctx>, post_id: PostId, save: bool) {
+    let req = SavePost { post_id, save };
+    if let Err(e) = ctx.api.save_post(&req).await {
+        ctx.send_update_action(UpdateAction::Error(Operation::SavePost(post_id, save), e));
+    }
+}
+
+pub async fn mark_post_read(ctx: Arc<Ctx>, post_id: PostId) {
+    let req = MarkPostAsRead {
+        post_ids: vec![post_id],
+        read: true,
+    };
+    if let Err(e) = ctx.api.mark_post_as_read(&req).await {
+        ctx.send_update_action(UpdateAction::Error(Operation::MarkPostRead(post_id), e));
+    }
+}
+
 impl Component for LemmynatorPost {
     fn handle_actions(&mut self, action: Action) {
         match action {
+            Action::VoteUp | Action::VoteDown | Action::ToggleSave if self.ctx.is_anonymous() => (),
             Action::VoteUp => {
                 self.vote(1);
                 self.ctx.send_action(Action::Render);
@@ -285,6 +402,11 @@ impl Component for LemmynatorPost {
                 self.vote(-1);
                 self.ctx.send_action(Action::Render);
             }
+            Action::ToggleSave => {
+                self.toggle_save();
+                self.ctx.send_action(Action::Render);
+            }
+            Action::RevealNsfw => self.reveal(),
             _ => (),
         }
     }
@@ -405,6 +527,14 @@ impl LemmynatorPost {
             ),
         ];
 
+        // Every comment of a post never opened is unread, that's no news.
+        if self.read && self.unread_comments > 0 {
+            spans.push(Span::styled(
+                format!("+{} new ", self.unread_comments),
+                Style::new().fg(CONFIG.general.accent_color),
+            ));
+        }
+
         if self.is_focused {
             spans.push(Span::raw(" "));
         }
@@ -442,6 +572,17 @@ impl LemmynatorPost {
             spans.push(Span::styled(" 󰐃", Style::new().green()))
         }
 
+        if self.nsfw {
+            spans.push(Span::styled(" NSFW", Style::new().red()))
+        }
+
+        if self.saved {
+            spans.push(Span::styled(
+                " ",
+                Style::new().fg(CONFIG.general.accent_color),
+            ))
+        }
+
         if self.name.chars().count() > 45 {
             let last_char_indice = self.name.char_indices().map(|(i, _)| i).nth(45).unwrap();
 
@@ -1,7 +1,28 @@
+mod feed;
+mod lemmy;
 mod lemmynator_comment;
 mod lemmynator_post;
+mod site_info;
 
+pub use feed::CommunityRef;
+pub use feed::Feed;
+pub use lemmy::Comment;
+pub use lemmy::Community;
+pub use lemmy::CommunityDetails;
+pub use lemmy::CommunityInfo;
+pub use lemmy::Person;
+pub use lemmy::PersonDetails;
+pub use lemmy::PersonInfo;
+pub use lemmy::Post;
+pub use lemmy::PostsPage;
+pub use lemmy::SearchResults;
+pub use lemmynator_comment::save_comment;
 pub use lemmynator_comment::CommentImage;
 pub use lemmynator_comment::LemmynatorPostComments;
 pub use lemmynator_comment::LemmynatorPostCommentsWidget;
+pub use lemmynator_post::load_image;
+pub use lemmynator_post::mark_post_read;
+pub use lemmynator_post::save_post;
 pub use lemmynator_post::LemmynatorPost;
+pub use lemmynator_post::ThreadImage;
+pub use site_info::SiteInfo;
@@ -0,0 +1,54 @@
+use std::collections::HashSet;
+
+use lemmy_api_common::lemmy_db_schema::{
+    newtypes::{CommunityId, InstanceId, PersonId},
+    ListingType, SortType,
+};
+use ln_config::Connection;
+
+/// What Lemmynator keeps from `GET /site`: the instance itself and the
+/// account's preferences, subscriptions and blocks.
+pub struct SiteInfo {
+    pub name: String,
+    pub icon: Option<String>,
+    pub default_listing_type: ListingType,
+    pub default_sort_type: SortType,
+    pub show_nsfw: bool,
+    pub show_read_posts: bool,
+    pub is_admin: bool,
+    /// Who's logged in, unless nobody is.
+    pub person_id: Option<PersonId>,
+    pub subscriptions: HashSet<CommunityId>,
+    pub blocked_communities: HashSet<CommunityId>,
+    pub blocked_people: HashSet<PersonId>,
+    pub blocked_instances: HashSet<InstanceId>,
+}
+
+impl Default for SiteInfo {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            icon: None,
+            default_listing_type: ListingType::Local,
+            default_sort_type: SortType::Hot,
+            show_nsfw: false,
+            show_read_posts: true,
+            is_admin: false,
+            person_id: None,
+            subscriptions: HashSet::new(),
+            blocked_communities: HashSet::new(),
+            blocked_people: HashSet::new(),
+            blocked_instances: HashSet::new(),
+        }
+    }
+}
+
+impl SiteInfo {
+    /// Used until the site is loaded, or when it can't be.
+    pub fn fallback(profile: &Connection) -> Self {
+        Self {
+            name: profile.instance.clone(),
+            ..Self::default()
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use intui_tabs::{Tabs, TabsState};
+use lemmy_api_common::{
+    community::ListCommunities,
+    lemmy_db_schema::{newtypes::CommunityId, SortType, SubscribedType},
+};
+use ln_config::CONFIG;
+use ratatui::{prelude::*, widgets::Paragraph};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    action::{Action, Operation, UpdateAction},
+    app::Ctx,
+    client::ClientError,
+    types::{CommunityInfo, ThreadImage},
+};
+
+use super::{
+    centered_rect,
+    community_view::{block_community, follow_community},
+    components::{
+        tabs::{CurrentTab, TabComponent},
+        Component,
+    },
+    listing::next_generation,
+};
+
+/// Height of a single community, including the gap below it.
+const ROW_HEIGHT: u16 = 3;
+
+struct BrowsedCommunity {
+    info: CommunityInfo,
+    icon: Arc<Mutex<Option<ThreadImage>>>,
+}
+
+/// Lists the communities known to the instance, page by page as the user scrolls.
+pub struct CommunityBrowser {
+    generation: u64,
+    cancel: CancellationToken,
+    tabs_state: TabsState<CurrentTab>,
+    tabs_count: usize,
+    sort_type: SortType,
+    communities: Vec<BrowsedCommunity>,
+    next_page: i64,
+    fetching: bool,
+    /// The last page didn't bring anything new.
+    exhausted: bool,
+    focused: usize,
+    offset: usize,
+    error: Option<ClientError>,
+    ctx: Arc<Ctx>,
+}
+
+impl CommunityBrowser {
+    pub fn new(ctx: Arc<Ctx>) -> Self {
+        let tabs = TabComponent::listing_tabs(&ctx);
+        let local_tab_idx = tabs
+            .iter()
+            .position(|tab| *tab == CurrentTab::Local)
+            .unwrap_or_default();
+        let tabs_count = tabs.len();
+        let mut tabs_state = TabsState::new(tabs);
+        tabs_state.set(local_tab_idx + 1);
+
+        let mut browser = Self {
+            generation: next_generation(),
+            cancel: CancellationToken::new(),
+            tabs_state,
+            tabs_count,
+            sort_type: SortType::TopAll,
+            communities: vec![],
+            next_page: 1,
+            fetching: false,
+            exhausted: false,
+            focused: 0,
+            offset: 0,
+            error: None,
+            ctx,
+        };
+        browser.fetch_more();
+        browser
+    }
+
+    pub fn sort_type(&self) -> SortType {
+        self.sort_type
+    }
+
+    pub fn set_sort(&mut self, sort_type: SortType) {
+        self.sort_type = sort_type;
+        self.reload();
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub fn focused_community_id(&self) -> Option<CommunityId> {
+        self.communities
+            .get(self.focused)
+            .map(|community| community.info.community.id)
+    }
+
+    /// Starts over, for example with another filter or sort.
+    fn reload(&mut self) {
+        self.cancel.cancel();
+        self.cancel = CancellationToken::new();
+        self.generation = next_generation();
+        self.communities.clear();
+        self.next_page = 1;
+        self.fetching = false;
+        self.exhausted = false;
+        self.focused = 0;
+        self.offset = 0;
+        self.error = None;
+        self.fetch_more();
+        self.ctx.send_action(Action::Render);
+    }
+
+    pub fn retry(&mut self) {
+        self.error = None;
+        self.fetch_more();
+        self.ctx.send_action(Action::Render);
+    }
+
+    fn fetch_more(&mut self) {
+        if self.fetching || self.exhausted || self.error.is_some() {
+            return;
+        }
+        self.fetching = true;
+
+        let req = ListCommunities {
+            type_: Some(self.tabs_state.current().as_listing_type()),
+            sort: Some(self.sort_type),
+            show_nsfw: Some(self.ctx.site().show_nsfw),
+            page: Some(self.next_page),
+            limit: Some(20),
+        };
+        let generation = self.generation;
+        let cancel = self.cancel.clone();
+        let ctx = Arc::clone(&self.ctx);
+
+        tokio::task::spawn(async move {
+            let res = tokio::select! {
+                _ = cancel.cancelled() => return,
+                res = ctx.api.list_communities(&req) => res,
+            };
+
+            match res {
+                Ok(page) => ctx.send_update_action(UpdateAction::CommunitiesPage(generation, page)),
+                Err(e) => ctx.send_update_action(UpdateAction::Error(
+                    Operation::FetchCommunities(generation),
+                    e,
+                )),
+            }
+            ctx.send_action(Action::Render);
+        });
+    }
+
+    fn append(&mut self, communities: Vec<CommunityInfo>) {
+        let known: HashSet<_> = self
+            .communities
+            .iter()
+            .map(|community| community.info.community.id)
+            .collect();
+        let new: Vec<_> = communities
+            .into_iter()
+            .filter(|info| !known.contains(&info.community.id))
+            .collect();
+
+        // Instances that don't paginate by page number keep sending the first one.
+        if new.is_empty() {
+            self.exhausted = true;
+            return;
+        }
+
+        for info in new {
+            let icon = Arc::new(Mutex::new(None));
+            if let Some(url) = &info.community.icon {
+                tokio::task::spawn(ThreadImage::fetch(
+                    url.to_string(),
+                    Arc::clone(&icon),
+                    Arc::clone(&self.ctx),
+                ));
+            }
+            self.communities.push(BrowsedCommunity { info, icon });
+        }
+    }
+
+    fn update_info(&mut self, info: CommunityInfo) {
+        if let Some(community) = self
+            .communities
+            .iter_mut()
+            .find(|community| community.info.community.id == info.community.id)
+        {
+            community.info = info;
+        }
+    }
+
+    fn focused_info(&self) -> Option<&CommunityInfo> {
+        self.communities
+            .get(self.focused)
+            .map(|community| &community.info)
+    }
+
+    fn scroll_down(&mut self) {
+        if self.focused + 1 < self.communities.len() {
+            self.focused += 1;
+        }
+        if self.focused + 5 >= self.communities.len() {
+            self.fetch_more();
+        }
+    }
+
+    fn row_lines(&self, community: &BrowsedCommunity, is_focused: bool) -> [Line<'static>; 2] {
+        let info = &community.info;
+        let accent = Style::default().fg(CONFIG.general.accent_color);
+
+        let mut title = vec![Span::styled(
+            format!(" {} ", info.community.title),
+            if is_focused {
+                accent.bold()
+            } else {
+                Style::new().white()
+            },
+        )];
+        match info.subscribed {
+            SubscribedType::Subscribed => title.push(Span::styled(" subscribed ", accent)),
+            SubscribedType::Pending => title.push(Span::raw(" pending ").dim()),
+            SubscribedType::NotSubscribed => (),
+        }
+        if info.blocked {
+            title.push(Span::styled(" blocked ", Style::new().red()));
+        }
+
+        let details = Line::from(format!(
+            " c/{}@{} · 󰀉 {} ·  {} active this week",
+            info.community.name, info.community.host, info.subscribers, info.users_active_week
+        ))
+        .dim();
+
+        [Line::from(title), details]
+    }
+
+    fn render_rows(&mut self, f: &mut Frame, rect: Rect) {
+        let visible = usize::from((rect.height / ROW_HEIGHT).max(1));
+        if self.focused < self.offset {
+            self.offset = self.focused;
+        } else if self.focused >= self.offset + visible {
+            self.offset = self.focused + 1 - visible;
+        }
+
+        let end = (self.offset + visible).min(self.communities.len());
+        for (row, idx) in (self.offset..end).enumerate() {
+            let row_rect = Rect {
+                y: rect.y + row as u16 * ROW_HEIGHT,
+                height: 2,
+                ..rect
+            };
+            let [icon_rect, _, text_rect] = Layout::horizontal([
+                Constraint::Length(4),
+                Constraint::Length(1),
+                Constraint::Fill(1),
+            ])
+            .areas(row_rect);
+
+            let community = &self.communities[idx];
+            if let Some(icon) = &mut *community.icon.lock().unwrap() {
+                icon.render(f, icon_rect, Arc::clone(&self.ctx));
+            } else {
+                f.render_widget(Paragraph::new("\n 󰀉").dim(), icon_rect);
+            }
+
+            let lines = self.row_lines(community, idx == self.focused);
+            f.render_widget(Paragraph::new(lines.to_vec()), text_rect);
+        }
+    }
+
+    fn render_message(&self, f: &mut Frame, rect: Rect, message: Line<'static>) {
+        let message_rect = centered_rect(rect, 80, 10);
+        f.render_widget(
+            Paragraph::new(message).alignment(Alignment::Center),
+            message_rect,
+        );
+    }
+
+    fn render_bottom_bar(&self, f: &mut Frame, rect: Rect) {
+        let key_style = Style::default()
+            .underlined()
+            .fg(CONFIG.general.accent_color);
+
+        let mut spans = vec![
+            Span::raw(" 󰋗 Press "),
+            Span::styled("Enter", key_style),
+            Span::raw(" to open, "),
+        ];
+        if !self.ctx.is_anonymous() {
+            spans.extend([
+                Span::styled("f", key_style),
+                Span::raw(" to subscribe, "),
+                Span::styled("B", key_style),
+                Span::raw(" to block, "),
+            ]);
+        }
+        spans.extend([
+            Span::styled("4", key_style),
+            Span::raw(" to sort, "),
+            Span::styled("q", key_style),
+            Span::raw(" to go back."),
+        ]);
+
+        f.render_widget(Paragraph::new(Line::from(spans)), rect);
+    }
+}
+
+impl Drop for CommunityBrowser {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+impl Component for CommunityBrowser {
+    fn handle_actions(&mut self, action: Action) {
+        match action {
+            Action::Up => {
+                self.focused = self.focused.saturating_sub(1);
+                self.ctx.send_action(Action::Render);
+            }
+            Action::Down => {
+                self.scroll_down();
+                self.ctx.send_action(Action::Render);
+            }
+            Action::ChangeSubTab(n) if usize::from(n) <= self.tabs_count => {
+                let previous = self.tabs_state.current();
+                self.tabs_state.set(n.into());
+                if self.tabs_state.current() != previous {
+                    self.reload();
+                }
+            }
+            Action::Retry if self.error.is_some() => self.retry(),
+            Action::ToggleFollow if !self.ctx.is_anonymous() => {
+                if let Some(info) = self.focused_info() {
+                    let follow = info.subscribed == SubscribedType::NotSubscribed;
+                    tokio::task::spawn(follow_community(
+                        Arc::clone(&self.ctx),
+                        info.community.id,
+                        follow,
+                    ));
+                }
+            }
+            Action::ToggleBlock if !self.ctx.is_anonymous() => {
+                if let Some(info) = self.focused_info() {
+                    tokio::task::spawn(block_community(
+                        Arc::clone(&self.ctx),
+                        info.community.id,
+                        !info.blocked,
+                    ));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn handle_update_action(&mut self, action: UpdateAction) {
+        match action {
+            UpdateAction::CommunitiesPage(generation, page) if generation == self.generation => {
+                self.fetching = false;
+                self.next_page += 1;
+                self.append(page);
+            }
+            UpdateAction::Error(Operation::FetchCommunities(generation), error)
+                if generation == self.generation =>
+            {
+                self.fetching = false;
+                self.error = Some(error);
+            }
+            UpdateAction::CommunityFollowed(info) | UpdateAction::CommunityBlocked(info) => {
+                self.update_info(*info);
+            }
+            _ => (),
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let [sub_tab_rect, _, list_rect, bottom_bar_rect] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(rect);
+
+        let tabs = Tabs::new()
+            .center(true)
+            .beginner_mode(true)
+            .sub_tab(true)
+            .color(CONFIG.general.accent_color);
+        f.render_stateful_widget(tabs, sub_tab_rect, &mut self.tabs_state);
+
+        if !self.communities.is_empty() {
+            self.render_rows(f, list_rect);
+        } else if let Some(error) = &self.error {
+            let message = Line::styled(
+                format!("Could not load communities: {error}"),
+                Style::new().red(),
+            );
+            self.render_message(f, list_rect, message);
+        } else if self.exhausted {
+            self.render_message(f, list_rect, Line::raw("No communities here.").dim());
+        } else {
+            self.render_message(f, list_rect, Line::raw("Loading communities...").dim());
+        }
+
+        self.render_bottom_bar(f, bottom_bar_rect);
+    }
+}
@@ -0,0 +1,386 @@
+use std::sync::Arc;
+
+use lemmy_api_common::{
+    community::{BlockCommunity, FollowCommunity, GetCommunity},
+    lemmy_db_schema::{newtypes::CommunityId, SortType, SubscribedType},
+};
+use ln_config::CONFIG;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, Borders, Paragraph, Wrap},
+};
+use ratskin::RatSkin;
+
+use crate::{
+    action::{Action, Operation, UpdateAction},
+    app::Ctx,
+    client::ClientError,
+    types::{CommunityDetails, CommunityRef, Feed},
+};
+
+use super::{
+    centered_rect,
+    components::{tabs::sort_name, Component},
+    listing::Listing,
+};
+
+/// A single community: its posts, and a sidebar with what it's about and who runs it.
+pub struct CommunityView {
+    target: CommunityRef,
+    details: Option<CommunityDetails>,
+    /// Opened by name, the posts can only be asked for once the details are in.
+    pub listing: Option<Listing>,
+    sort_type: SortType,
+    error: Option<ClientError>,
+    ctx: Arc<Ctx>,
+}
+
+impl CommunityView {
+    pub fn new(target: CommunityRef, sort_type: SortType, ctx: Arc<Ctx>) -> Self {
+        let listing = match target {
+            CommunityRef::Id(community_id) => Some(
+                Listing::new(Feed::Community(community_id), sort_type, Arc::clone(&ctx)).unwrap(),
+            ),
+            CommunityRef::Name(_) => None,
+        };
+
+        let community_view = Self {
+            target,
+            details: None,
+            listing,
+            sort_type,
+            error: None,
+            ctx,
+        };
+        community_view.fetch_details();
+        community_view
+    }
+
+    pub fn target(&self) -> &CommunityRef {
+        &self.target
+    }
+
+    pub fn community_id(&self) -> Option<CommunityId> {
+        match &self.target {
+            CommunityRef::Id(community_id) => Some(*community_id),
+            CommunityRef::Name(_) => self
+                .details
+                .as_ref()
+                .map(|details| details.info.community.id),
+        }
+    }
+
+    pub fn sort_type(&self) -> SortType {
+        self.sort_type
+    }
+
+    pub fn set_sort(&mut self, sort_type: SortType) {
+        self.sort_type = sort_type;
+        if let Some(community_id) = self.community_id() {
+            self.listing = Some(
+                Listing::new(
+                    Feed::Community(community_id),
+                    sort_type,
+                    Arc::clone(&self.ctx),
+                )
+                .unwrap(),
+            );
+        }
+        self.ctx.send_action(Action::Render);
+    }
+
+    pub fn fetch_details(&self) {
+        let req = match &self.target {
+            CommunityRef::Id(community_id) => GetCommunity {
+                id: Some(*community_id),
+                name: None,
+            },
+            CommunityRef::Name(name) => GetCommunity {
+                id: None,
+                name: Some(name.clone()),
+            },
+        };
+
+        let target = self.target.clone();
+        let ctx = Arc::clone(&self.ctx);
+        tokio::task::spawn(async move {
+            match ctx.api.get_community(&req).await {
+                Ok(res) => {
+                    ctx.send_update_action(UpdateAction::CommunityDetails(target, Box::new(res)))
+                }
+                Err(e) => ctx
+                    .send_update_action(UpdateAction::Error(Operation::FetchCommunity(target), e)),
+            }
+            ctx.send_action(Action::Render);
+        });
+    }
+
+    fn toggle_follow(&self) {
+        let Some(details) = &self.details else {
+            return;
+        };
+        if self.ctx.is_anonymous() {
+            return;
+        }
+
+        // Pending subscriptions to remote communities can be cancelled as well.
+        let follow = details.info.subscribed == SubscribedType::NotSubscribed;
+        tokio::task::spawn(follow_community(
+            Arc::clone(&self.ctx),
+            details.info.community.id,
+            follow,
+        ));
+    }
+
+    fn toggle_block(&self) {
+        let Some(details) = &self.details else {
+            return;
+        };
+        if self.ctx.is_anonymous() {
+            return;
+        }
+
+        tokio::task::spawn(block_community(
+            Arc::clone(&self.ctx),
+            details.info.community.id,
+            !details.info.blocked,
+        ));
+    }
+
+    fn sidebar_lines(&self, width: u16) -> Vec<Line<'static>> {
+        let Some(details) = &self.details else {
+            return vec![];
+        };
+        let info = &details.info;
+        let community = &info.community;
+        let accent = Style::default().fg(CONFIG.general.accent_color);
+        let key_style = Style::default()
+            .underlined()
+            .fg(CONFIG.general.accent_color);
+
+        let mut lines = vec![
+            Line::styled(community.title.clone(), accent.bold()),
+            Line::raw(format!("c/{}@{}", community.name, community.host)).dim(),
+            Line::default(),
+            Line::raw(format!("󰀉 {} subscribers", info.subscribers)),
+            Line::raw(format!(
+                " {} active this week, {} today",
+                info.users_active_week, info.users_active_day
+            )),
+            Line::raw(format!(
+                " Sorted by {}",
+                sort_name(self.sort_type).to_lowercase()
+            )),
+            Line::default(),
+        ];
+
+        if !self.ctx.is_anonymous() {
+            let (status, hint) = match info.subscribed {
+                SubscribedType::Subscribed => ("Subscribed", " to unsubscribe"),
+                SubscribedType::Pending => ("Subscription pending", " to cancel"),
+                SubscribedType::NotSubscribed => ("Not subscribed", " to subscribe"),
+            };
+            lines.push(Line::from(vec![
+                Span::raw(format!("{status}, press ")),
+                Span::styled("f", key_style),
+                Span::raw(hint),
+            ]));
+            let (status, hint) = match info.blocked {
+                true => ("Blocked", " to unblock"),
+                false => ("Not blocked", " to block"),
+            };
+            lines.push(Line::from(vec![
+                Span::raw(format!("{status}, press ")),
+                Span::styled("B", key_style),
+                Span::raw(hint),
+            ]));
+            lines.push(Line::default());
+        }
+
+        if let Some(description) = &info.description {
+            let rat_skin = RatSkin::default();
+            let text = RatSkin::parse_text(description);
+            lines.extend(rat_skin.parse(text, width));
+            lines.push(Line::default());
+        }
+
+        if !details.moderators.is_empty() {
+            lines.push(Line::styled("Moderators", accent.bold()));
+            lines.extend(
+                details
+                    .moderators
+                    .iter()
+                    .map(|moderator| Line::raw(format!("u/{}", moderator.name))),
+            );
+        }
+
+        lines
+    }
+
+    fn render_error_screen(&self, f: &mut Frame, rect: Rect, error: &ClientError) {
+        let error_rect = centered_rect(rect, 80, 10);
+        let error_paragraph = Paragraph::new(vec![
+            Line::styled(
+                format!("Could not load the community: {error}"),
+                Style::new().red(),
+            ),
+            Line::from(vec![
+                Span::raw("Press "),
+                Span::styled(
+                    "r",
+                    Style::default()
+                        .underlined()
+                        .fg(CONFIG.general.accent_color),
+                ),
+                Span::raw(" to try again or "),
+                Span::styled(
+                    "q",
+                    Style::default()
+                        .underlined()
+                        .fg(CONFIG.general.accent_color),
+                ),
+                Span::raw(" to go back."),
+            ]),
+        ])
+        .alignment(Alignment::Center);
+        f.render_widget(error_paragraph, error_rect);
+    }
+}
+
+/// Subscribes to or unsubscribes from a community, keeping the known subscriptions in sync.
+pub async fn follow_community(ctx: Arc<Ctx>, community_id: CommunityId, follow: bool) {
+    let req = FollowCommunity {
+        community_id,
+        follow,
+    };
+
+    match ctx.api.follow_community(&req).await {
+        Ok(info) => {
+            {
+                let mut site = ctx.site_mut();
+                if info.subscribed == SubscribedType::Subscribed {
+                    site.subscriptions.insert(community_id);
+                } else {
+                    site.subscriptions.remove(&community_id);
+                }
+            }
+            ctx.send_update_action(UpdateAction::CommunityFollowed(Box::new(info)));
+        }
+        Err(e) => ctx.send_update_action(UpdateAction::Error(
+            Operation::FollowCommunity(community_id, follow),
+            e,
+        )),
+    }
+    ctx.send_action(Action::Render);
+}
+
+/// Blocks or unblocks a community, keeping the known blocks in sync.
+pub async fn block_community(ctx: Arc<Ctx>, community_id: CommunityId, block: bool) {
+    let req = BlockCommunity {
+        community_id,
+        block,
+    };
+
+    match ctx.api.block_community(&req).await {
+        Ok(info) => {
+            {
+                let mut site = ctx.site_mut();
+                if info.blocked {
+                    site.blocked_communities.insert(community_id);
+                } else {
+                    site.blocked_communities.remove(&community_id);
+                }
+            }
+            ctx.send_update_action(UpdateAction::CommunityBlocked(Box::new(info)));
+        }
+        Err(e) => ctx.send_update_action(UpdateAction::Error(
+            Operation::BlockCommunity(community_id, block),
+            e,
+        )),
+    }
+    ctx.send_action(Action::Render);
+}
+
+impl Component for CommunityView {
+    fn handle_actions(&mut self, action: Action) {
+        match action {
+            Action::ToggleFollow => self.toggle_follow(),
+            Action::ToggleBlock => self.toggle_block(),
+            Action::Retry if self.error.is_some() => {
+                self.error = None;
+                self.fetch_details();
+            }
+            _ => {
+                if let Some(listing) = &mut self.listing {
+                    listing.handle_actions(action);
+                }
+            }
+        }
+    }
+
+    fn handle_update_action(&mut self, action: UpdateAction) {
+        match action {
+            UpdateAction::CommunityDetails(target, details) if target == self.target => {
+                if self.listing.is_none() {
+                    self.listing = Some(
+                        Listing::new(
+                            Feed::Community(details.info.community.id),
+                            self.sort_type,
+                            Arc::clone(&self.ctx),
+                        )
+                        .unwrap(),
+                    );
+                }
+                self.details = Some(*details);
+                self.error = None;
+            }
+            UpdateAction::CommunityFollowed(info) | UpdateAction::CommunityBlocked(info) => {
+                if let Some(details) = &mut self.details {
+                    if details.info.community.id == info.community.id {
+                        details.info = *info;
+                    }
+                }
+            }
+            UpdateAction::Error(Operation::FetchCommunity(target), error)
+                if target == self.target =>
+            {
+                self.error = Some(error);
+            }
+            UpdateAction::NewPage(Feed::Community(_), _, _)
+            | UpdateAction::Error(Operation::FetchPage(Feed::Community(_), _), _) => {
+                if let Some(listing) = &mut self.listing {
+                    listing.handle_update_action(action);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        if let (None, Some(error)) = (&self.details, &self.error) {
+            self.render_error_screen(f, rect, error);
+            return;
+        }
+
+        let [listing_rect, _, sidebar_rect] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(2),
+            Constraint::Length(40),
+        ])
+        .areas(rect);
+
+        if let Some(listing) = &mut self.listing {
+            listing.render(f, listing_rect);
+        }
+
+        let block = Block::new()
+            .borders(Borders::LEFT)
+            .border_style(Style::new().dim());
+        let inner_rect = block.inner(sidebar_rect).inner(Margin::new(1, 0));
+        f.render_widget(block, sidebar_rect);
+
+        let sidebar =
+            Paragraph::new(self.sidebar_lines(inner_rect.width)).wrap(Wrap { trim: false });
+        f.render_widget(sidebar, inner_rect);
+    }
+}
@@ -0,0 +1,217 @@
+use std::sync::Arc;
+
+use lemmy_api_common::{
+    lemmy_db_schema::newtypes::{CommunityId, PersonId, PostId},
+    post::GetPost,
+};
+use ln_config::CONFIG;
+use ratatui::{prelude::*, widgets::Paragraph};
+
+use crate::{
+    action::{Action, Operation, UpdateAction},
+    app::Ctx,
+    types::{Comment, CommunityInfo, CommunityRef, LemmynatorPost, PersonInfo, Post},
+};
+
+use super::Component;
+
+/// Height of a single entry, including the gap below it.
+const ROW_HEIGHT: u16 = 3;
+
+/// Anything Lemmy can list, shown as a title and a line of details.
+pub enum Entry {
+    Post(Box<Post>),
+    Comment(Box<Comment>),
+    Community(Box<CommunityInfo>),
+    User(Box<PersonInfo>),
+}
+
+impl Entry {
+    /// The community the entry comes from or is.
+    pub fn community_id(&self) -> Option<CommunityId> {
+        match self {
+            Entry::Post(post) => Some(post.community.id),
+            Entry::Comment(comment) => Some(comment.community.id),
+            Entry::Community(info) => Some(info.community.id),
+            Entry::User(_) => None,
+        }
+    }
+
+    /// Who wrote the entry, or who it is.
+    pub fn person_id(&self) -> Option<PersonId> {
+        match self {
+            Entry::Post(post) => Some(post.creator.id),
+            Entry::Comment(comment) => Some(comment.creator.id),
+            Entry::Community(_) => None,
+            Entry::User(info) => Some(info.person.id),
+        }
+    }
+
+    /// Opens the view of the entry, a comment opens the post it was left under.
+    pub fn open(&self, ctx: &Arc<Ctx>) {
+        match self {
+            Entry::Post(post) => {
+                let post = LemmynatorPost::from_lemmy_post((**post).clone(), Arc::clone(ctx));
+                ctx.send_update_action(UpdateAction::ViewPost(Box::new(post)));
+            }
+            Entry::Comment(comment) => {
+                tokio::task::spawn(view_post(Arc::clone(ctx), comment.post_id));
+            }
+            Entry::Community(info) => ctx.send_update_action(UpdateAction::ViewCommunity(
+                CommunityRef::Id(info.community.id),
+            )),
+            Entry::User(info) => ctx.send_update_action(UpdateAction::ViewPerson(info.person.id)),
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            Entry::Post(_) => "post",
+            Entry::Comment(_) => "comment",
+            Entry::Community(_) => "community",
+            Entry::User(_) => "user",
+        }
+    }
+
+    fn title_and_details(&self) -> (String, String) {
+        match self {
+            Entry::Post(post) => (
+                post.name.clone(),
+                format!(
+                    "c/{} · u/{} · {} points · {} comments",
+                    post.community.name, post.creator.name, post.score, post.comments
+                ),
+            ),
+            Entry::Comment(comment) => (
+                comment
+                    .content
+                    .lines()
+                    .find(|line| !line.trim().is_empty())
+                    .unwrap_or_default()
+                    .to_string(),
+                format!(
+                    "u/{} on \"{}\" in c/{}",
+                    comment.creator.name, comment.post_name, comment.community.name
+                ),
+            ),
+            Entry::Community(info) => (
+                info.community.title.clone(),
+                format!(
+                    "c/{}@{} · {} subscribers",
+                    info.community.name, info.community.host, info.subscribers
+                ),
+            ),
+            Entry::User(info) => (
+                info.person
+                    .display_name
+                    .clone()
+                    .unwrap_or_else(|| info.person.name.clone()),
+                format!(
+                    "u/{}@{} · {} posts · {} comments",
+                    info.person.name, info.person.host, info.post_count, info.comment_count
+                ),
+            ),
+        }
+    }
+}
+
+/// Opens a post known only by its id, like the one a comment was left under.
+pub async fn view_post(ctx: Arc<Ctx>, post_id: PostId) {
+    let req = GetPost {
+        id: Some(post_id),
+        comment_id: None,
+    };
+
+    match ctx.api.get_post(&req).await {
+        Ok(post) => {
+            let post = LemmynatorPost::from_lemmy_post(post, Arc::clone(&ctx));
+            ctx.send_update_action(UpdateAction::ViewPost(Box::new(post)));
+        }
+        Err(e) => ctx.send_update_action(UpdateAction::Error(Operation::FetchPost(post_id), e)),
+    }
+    ctx.send_action(Action::Render);
+}
+
+/// A scrollable list of entries, one of them in focus.
+#[derive(Default)]
+pub struct EntryList {
+    pub entries: Vec<Entry>,
+    /// Tells what each entry is, for lists mixing them.
+    show_kinds: bool,
+    focused: usize,
+    offset: usize,
+}
+
+impl EntryList {
+    pub fn new(entries: Vec<Entry>, show_kinds: bool) -> Self {
+        Self {
+            entries,
+            show_kinds,
+            focused: 0,
+            offset: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn focused(&self) -> Option<&Entry> {
+        self.entries.get(self.focused)
+    }
+
+    /// How many entries come after the one in focus.
+    pub fn remaining(&self) -> usize {
+        self.entries.len().saturating_sub(self.focused + 1)
+    }
+
+    fn row_lines(&self, entry: &Entry, is_focused: bool) -> [Line<'static>; 2] {
+        let title_style = if is_focused {
+            Style::default().fg(CONFIG.general.accent_color).bold()
+        } else {
+            Style::new().white()
+        };
+        let (title, details) = entry.title_and_details();
+
+        let mut title_spans = vec![];
+        if self.show_kinds {
+            title_spans.push(Span::raw(format!(" {} ", entry.kind())).dim());
+        }
+        title_spans.push(Span::styled(format!(" {title}"), title_style));
+
+        [
+            Line::from(title_spans),
+            Line::raw(format!(" {details}")).dim(),
+        ]
+    }
+}
+
+impl Component for EntryList {
+    fn handle_actions(&mut self, action: Action) {
+        match action {
+            Action::Up => self.focused = self.focused.saturating_sub(1),
+            Action::Down if self.focused + 1 < self.entries.len() => self.focused += 1,
+            _ => (),
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let visible = usize::from((rect.height / ROW_HEIGHT).max(1));
+        if self.focused < self.offset {
+            self.offset = self.focused;
+        } else if self.focused >= self.offset + visible {
+            self.offset = self.focused + 1 - visible;
+        }
+
+        let end = (self.offset + visible).min(self.entries.len());
+        for (row, idx) in (self.offset..end).enumerate() {
+            let row_rect = Rect {
+                y: rect.y + row as u16 * ROW_HEIGHT,
+                height: 2,
+                ..rect
+            };
+            let lines = self.row_lines(&self.entries[idx], idx == self.focused);
+            f.render_widget(Paragraph::new(lines.to_vec()), row_rect);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use crossterm::event::{Event, KeyCode, KeyEvent};
+use ln_config::CONFIG;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, BorderType, Clear, Paragraph},
+};
+use tui_input::{backend::crossterm::EventHandler, Input};
+
+use super::Component;
+
+pub enum InputOutcome {
+    Submitted(String),
+    Cancelled,
+    Pending,
+}
+
+/// Single line text field drawn as a small popup in the middle of the screen.
+pub struct InputPopup {
+    input: Input,
+    title: String,
+    message: Option<Line<'static>>,
+}
+
+impl InputPopup {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            input: Input::default(),
+            title: title.into(),
+            message: None,
+        }
+    }
+
+    pub fn set_message(&mut self, message: Option<Line<'static>>) {
+        self.message = message;
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> InputOutcome {
+        match key.code {
+            KeyCode::Enter if !self.input.value().trim().is_empty() => {
+                let value = self.input.value().trim().to_string();
+                self.input.reset();
+                InputOutcome::Submitted(value)
+            }
+            KeyCode::Esc => InputOutcome::Cancelled,
+            _ => {
+                self.input.handle_event(&Event::Key(key));
+                InputOutcome::Pending
+            }
+        }
+    }
+}
+
+impl Component for InputPopup {
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let height = if self.message.is_some() { 4 } else { 3 };
+        let [popup_rect] = Layout::vertical([Constraint::Length(height)])
+            .flex(layout::Flex::Center)
+            .areas(rect);
+        let [popup_rect] = Layout::horizontal([Constraint::Percentage(50)])
+            .flex(layout::Flex::Center)
+            .areas(popup_rect);
+
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(CONFIG.general.accent_color))
+            .title(format!(" {} ", self.title));
+
+        let inner_rect = block.inner(popup_rect);
+        let [input_rect, message_rect] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner_rect);
+
+        let scroll = self.input.visual_scroll(input_rect.width as usize);
+
+        f.render_widget(Clear, popup_rect);
+        f.render_widget(block, popup_rect);
+        f.render_widget(
+            Paragraph::new(self.input.value()).scroll((0, scroll as u16)),
+            input_rect,
+        );
+
+        if let Some(message) = &self.message {
+            f.render_widget(Paragraph::new(message.clone()), message_rect);
+        }
+
+        f.set_cursor_position((
+            input_rect.x + (self.input.visual_cursor().saturating_sub(scroll)) as u16,
+            input_rect.y,
+        ));
+    }
+}
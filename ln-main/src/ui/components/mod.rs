@@ -1,3 +1,6 @@
+pub mod entry_list;
+pub mod input;
+pub mod picker;
 pub mod tabs;
 
 use ratatui::prelude::*;
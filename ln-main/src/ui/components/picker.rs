@@ -0,0 +1,118 @@
+use ln_config::CONFIG;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, BorderType, Clear, List, ListItem, ListState},
+};
+
+use crate::action::Action;
+
+use super::Component;
+
+pub enum PickerOutcome {
+    Picked(usize),
+    Cancelled,
+    Pending,
+}
+
+/// Popup letting the user pick one entry out of a short list.
+pub struct Picker {
+    title: String,
+    items: Vec<Line<'static>>,
+    keys: Vec<Option<char>>,
+    state: ListState,
+}
+
+impl Picker {
+    pub fn new(title: impl Into<String>, items: Vec<Line<'static>>, selected: usize) -> Self {
+        Self {
+            title: title.into(),
+            keys: vec![None; items.len()],
+            items,
+            state: ListState::default().with_selected(Some(selected)),
+        }
+    }
+
+    /// Lets entries be picked right away with a key, which is shown in front of them.
+    pub fn with_keys(mut self, keys: Vec<Option<char>>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    pub fn handle_action(&mut self, action: &Action) -> PickerOutcome {
+        match action {
+            Action::Up => self.state.select_previous(),
+            Action::Down if self.state.selected() < Some(self.items.len().saturating_sub(1)) => {
+                self.state.select_next()
+            }
+            Action::Confirm => {
+                return self
+                    .state
+                    .selected()
+                    .map_or(PickerOutcome::Pending, PickerOutcome::Picked)
+            }
+            Action::Char(c) => {
+                if let Some(idx) = self.keys.iter().position(|key| *key == Some(*c)) {
+                    return PickerOutcome::Picked(idx);
+                }
+            }
+            Action::Quit | Action::Dismiss => return PickerOutcome::Cancelled,
+            _ => (),
+        }
+        PickerOutcome::Pending
+    }
+}
+
+impl Component for Picker {
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let key_style = Style::default()
+            .underlined()
+            .underline_color(CONFIG.general.accent_color);
+        let has_keys = self.keys.iter().any(Option::is_some);
+        let items: Vec<Line> = self
+            .items
+            .iter()
+            .zip(&self.keys)
+            .map(|(item, key)| {
+                if !has_keys {
+                    return item.clone();
+                }
+                let key = match key {
+                    Some(key) => Span::styled(key.to_string(), key_style),
+                    None => Span::raw(" "),
+                };
+                let mut spans = vec![key, Span::raw("  ")];
+                spans.extend(item.spans.iter().cloned());
+                Line::from(spans)
+            })
+            .collect();
+
+        let width = items
+            .iter()
+            .map(Line::width)
+            .chain(std::iter::once(self.title.len()))
+            .max()
+            .unwrap_or_default() as u16
+            + 6;
+        let height = self.items.len() as u16 + 2;
+
+        let [popup_rect] = Layout::vertical([Constraint::Length(height)])
+            .flex(layout::Flex::Center)
+            .areas(rect);
+        let [popup_rect] = Layout::horizontal([Constraint::Length(width)])
+            .flex(layout::Flex::Center)
+            .areas(popup_rect);
+
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(Style::default().fg(CONFIG.general.accent_color))
+            .title(format!(" {} ", self.title));
+
+        let list = List::new(items.into_iter().map(ListItem::new))
+            .block(block)
+            .highlight_symbol(" ")
+            .highlight_style(Style::new().fg(CONFIG.general.accent_color).bold());
+
+        f.render_widget(Clear, popup_rect);
+        f.render_stateful_widget(list, popup_rect, &mut self.state);
+    }
+}
@@ -1,18 +1,28 @@
-use std::{fmt::Display, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    sync::Arc,
+};
 
-use crate::{action::Action, app::Ctx};
+use crate::{action::Action, app::Ctx, types::Feed};
 
 use super::Component;
-use intui_tabs::{Tabs, TabsState};
+use intui_tabs::TabsState;
 use lemmy_api_common::lemmy_db_schema::{ListingType, SortType};
-use ln_config::CONFIG;
-use ratatui::{layout::Flex, prelude::*, widgets::Paragraph};
+use ln_config::{Tab, CONFIG};
+use ratatui::{
+    layout::Flex,
+    prelude::*,
+    widgets::{Paragraph, Tabs},
+};
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub enum CurrentTab {
     Subscribed = 0,
+    #[default]
     Local,
     All,
+    Saved,
 }
 
 impl From<ListingType> for CurrentTab {
@@ -26,19 +36,66 @@ impl From<ListingType> for CurrentTab {
     }
 }
 
+impl From<Tab> for CurrentTab {
+    fn from(value: Tab) -> Self {
+        match value {
+            Tab::Subscribed => Self::Subscribed,
+            Tab::Local => Self::Local,
+            Tab::All => Self::All,
+        }
+    }
+}
+
 impl CurrentTab {
+    /// The tab showing the given feed, if any does.
+    pub fn from_feed(feed: Feed) -> Option<Self> {
+        match feed {
+            Feed::Listing(listing_type) => Some(listing_type.into()),
+            Feed::Community(_) => None,
+            Feed::Saved => Some(Self::Saved),
+        }
+    }
+
+    fn as_config_tab(&self) -> Option<Tab> {
+        match self {
+            CurrentTab::Subscribed => Some(Tab::Subscribed),
+            CurrentTab::Local => Some(Tab::Local),
+            CurrentTab::All => Some(Tab::All),
+            CurrentTab::Saved => None,
+        }
+    }
+
+    /// The configured sort of this tab, or the account's or instance's default.
+    pub fn default_sort(&self, ctx: &Ctx) -> SortType {
+        self.as_config_tab()
+            .and_then(|tab| CONFIG.feed.sort(tab))
+            .and_then(|sort| sort.parse().ok())
+            .unwrap_or(ctx.site().default_sort_type)
+    }
+
+    /// Saved posts come from anywhere.
     pub fn as_listing_type(&self) -> ListingType {
         match self {
             CurrentTab::Subscribed => ListingType::Subscribed,
             CurrentTab::Local => ListingType::Local,
-            CurrentTab::All => ListingType::All,
+            CurrentTab::All | CurrentTab::Saved => ListingType::All,
         }
     }
-}
 
-impl Default for CurrentTab {
-    fn default() -> Self {
-        CurrentTab::Local
+    /// The key opening the tab at `idx`. The saved posts keep theirs no
+    /// matter how many tabs come before, as `4` picks the sort.
+    pub fn key(&self, idx: usize) -> u8 {
+        match self {
+            CurrentTab::Saved => SAVED_TAB_KEY,
+            _ => idx as u8 + 1,
+        }
+    }
+
+    pub fn feed(&self) -> Feed {
+        match self {
+            CurrentTab::Saved => Feed::Saved,
+            tab => Feed::Listing(tab.as_listing_type()),
+        }
     }
 }
 
@@ -48,45 +105,179 @@ impl Display for CurrentTab {
             CurrentTab::Subscribed => write!(f, "Subscribed"),
             CurrentTab::Local => write!(f, "Local"),
             CurrentTab::All => write!(f, "All"),
+            CurrentTab::Saved => write!(f, "Saved"),
         }
     }
 }
 
+const SAVED_TAB_KEY: u8 = 5;
+
+/// Every sort in the order it's offered, with the key picking it in the sort picker.
+pub const SORT_TYPES: [(SortType, char); 19] = [
+    (SortType::Hot, 'h'),
+    (SortType::Active, 'a'),
+    (SortType::Scaled, 's'),
+    (SortType::Controversial, 'c'),
+    (SortType::New, 'n'),
+    (SortType::Old, 'o'),
+    (SortType::MostComments, 'm'),
+    (SortType::NewComments, 'N'),
+    (SortType::TopHour, 'H'),
+    (SortType::TopSixHour, '6'),
+    (SortType::TopTwelveHour, 'T'),
+    (SortType::TopDay, 'd'),
+    (SortType::TopWeek, 'w'),
+    (SortType::TopMonth, 'M'),
+    (SortType::TopThreeMonths, 'Q'),
+    (SortType::TopSixMonths, 'S'),
+    (SortType::TopNineMonths, '9'),
+    (SortType::TopYear, 'y'),
+    (SortType::TopAll, 'A'),
+];
+
+/// The time window of `Top*` sorts, `None` for all the others.
+pub fn top_window(sort: SortType) -> Option<&'static str> {
+    match sort {
+        SortType::TopHour => Some("Hour"),
+        SortType::TopSixHour => Some("6 hours"),
+        SortType::TopTwelveHour => Some("12 hours"),
+        SortType::TopDay => Some("Day"),
+        SortType::TopWeek => Some("Week"),
+        SortType::TopMonth => Some("Month"),
+        SortType::TopThreeMonths => Some("3 months"),
+        SortType::TopSixMonths => Some("6 months"),
+        SortType::TopNineMonths => Some("9 months"),
+        SortType::TopYear => Some("Year"),
+        SortType::TopAll => Some("All time"),
+        _ => None,
+    }
+}
+
+pub fn sort_name(sort: SortType) -> String {
+    match (sort, top_window(sort)) {
+        (_, Some(window)) => format!("Top {}", window.to_lowercase()),
+        (SortType::MostComments, _) => "Most comments".to_string(),
+        (SortType::NewComments, _) => "New comments".to_string(),
+        (sort, _) => sort.to_string(),
+    }
+}
+
 pub struct TabComponent {
     pub tabs_state: TabsState<CurrentTab>,
-    pub current_sort: SortType,
+    tabs: Vec<CurrentTab>,
+    /// Every tab keeps its own sort.
+    sorts: HashMap<CurrentTab, SortType>,
+    /// Tabs whose sort the user picked, which the defaults no longer apply to.
+    picked_sorts: HashSet<CurrentTab>,
+    /// Whether the user switched tabs, so the default tab is no longer wanted.
+    picked_tab: bool,
     ctx: Arc<Ctx>,
 }
 
 impl TabComponent {
     pub fn new(ctx: Arc<Ctx>) -> Self {
+        let tabs = Self::available_tabs(&ctx);
+        let sorts = tabs
+            .iter()
+            .map(|tab| (*tab, tab.default_sort(&ctx)))
+            .collect();
+        let default_tab_idx = Self::default_tab_idx(&ctx, &tabs);
+
+        let mut tabs_state = TabsState::new(tabs.clone());
+        tabs_state.set(default_tab_idx + 1);
+
         Self {
-            current_sort: SortType::Hot,
+            sorts,
+            picked_sorts: HashSet::new(),
+            picked_tab: false,
+            tabs,
+            tabs_state,
             ctx,
-            tabs_state: TabsState::new(vec![
-                CurrentTab::Subscribed,
-                CurrentTab::Local,
-                CurrentTab::All,
-            ]),
         }
     }
 
+    fn default_tab_idx(ctx: &Ctx, tabs: &[CurrentTab]) -> usize {
+        let default_tab = CONFIG
+            .feed
+            .default_tab
+            .map(CurrentTab::from)
+            .unwrap_or_else(|| CurrentTab::from(ctx.site().default_listing_type));
+        // Guests can't open their subscriptions, even if that's the instance's default.
+        tabs.iter()
+            .position(|tab| *tab == default_tab)
+            .or_else(|| tabs.iter().position(|tab| *tab == CurrentTab::Local))
+            .unwrap_or_default()
+    }
+
+    /// Applies the instance's and the account's defaults once they're known,
+    /// except to what the user already picked or to the `kept` tabs, and adds
+    /// the tabs a freshly logged in account has. Returns the tabs whose
+    /// listing has to be set up again.
+    pub fn apply_site_defaults(&mut self, kept: &HashSet<CurrentTab>) -> Vec<CurrentTab> {
+        let tabs = Self::available_tabs(&self.ctx);
+
+        let mut changed = vec![];
+        for tab in &tabs {
+            let is_new = !self.sorts.contains_key(tab);
+            if !is_new && (self.picked_sorts.contains(tab) || kept.contains(tab)) {
+                continue;
+            }
+            let sort = tab.default_sort(&self.ctx);
+            if self.sorts.insert(*tab, sort) != Some(sort) {
+                changed.push(*tab);
+            }
+        }
+
+        let current = self.tabs_state.current();
+        let tab_idx = match tabs.iter().position(|tab| *tab == current) {
+            Some(idx) if self.picked_tab || kept.contains(&current) => idx,
+            _ => Self::default_tab_idx(&self.ctx, &tabs),
+        };
+        self.tabs_state = TabsState::new(tabs.clone());
+        self.tabs_state.set(tab_idx + 1);
+        self.tabs = tabs;
+
+        changed
+    }
+
+    /// Subscriptions only make sense with an account.
+    pub fn listing_tabs(ctx: &Ctx) -> Vec<CurrentTab> {
+        if ctx.is_anonymous() {
+            vec![CurrentTab::Local, CurrentTab::All]
+        } else {
+            vec![CurrentTab::Subscribed, CurrentTab::Local, CurrentTab::All]
+        }
+    }
+
+    /// The listing tabs, followed by the saved posts if they're asked for.
+    pub fn available_tabs(ctx: &Ctx) -> Vec<CurrentTab> {
+        let mut tabs = Self::listing_tabs(ctx);
+        if CONFIG.feed.saved_tab && !ctx.is_anonymous() {
+            tabs.push(CurrentTab::Saved);
+        }
+        tabs
+    }
+
     pub fn current_sort(&self) -> SortType {
-        self.current_sort
+        self.sort(self.tabs_state.current())
+    }
+
+    pub fn sort(&self, tab: CurrentTab) -> SortType {
+        self.sorts[&tab]
     }
 
     pub fn current_listing_type(&self) -> ListingType {
         self.tabs_state.current().as_listing_type()
     }
 
-    pub fn change_sort(&mut self) {
-        match self.current_sort {
-            SortType::Hot => self.current_sort = SortType::Active,
-            SortType::Active => self.current_sort = SortType::Scaled,
-            SortType::Scaled => self.current_sort = SortType::Controversial,
-            SortType::New => self.current_sort = SortType::Hot,
-            _ => unreachable!(),
-        }
+    pub fn current_feed(&self) -> Feed {
+        self.tabs_state.current().feed()
+    }
+
+    pub fn set_sort(&mut self, sort: SortType) {
+        let tab = self.tabs_state.current();
+        self.sorts.insert(tab, sort);
+        self.picked_sorts.insert(tab);
     }
 }
 
@@ -94,28 +285,54 @@ impl Component for TabComponent {
     fn render(&mut self, f: &mut Frame, rect: Rect) {
         let [top_bar] = Layout::vertical([Constraint::Length(1)]).areas(rect);
 
-        let sort_string = format!(" {}  ", self.current_sort().to_string());
+        let sort_string = format!(" {}  ", sort_name(self.current_sort()));
+
+        // Drawn like `intui_tabs` does, but labelled with the tabs' keys
+        // rather than their positions.
+        let key_style = Style::default()
+            .underlined()
+            .underline_color(CONFIG.general.accent_color);
+        let tab_lines: Vec<Line> = self
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(idx, tab)| {
+                Line::from(vec![
+                    Span::styled(tab.key(idx).to_string(), key_style),
+                    Span::raw(format!(". {tab}")),
+                ])
+            })
+            .collect();
+        let tabs_width = tab_lines.iter().map(|line| line.width() + 3).sum::<usize>();
+        let current_idx = self
+            .tabs
+            .iter()
+            .position(|tab| *tab == self.tabs_state.current())
+            .unwrap_or_default();
 
         let [listing_type_rect, separator_rect, sort_type_rect] = Layout::horizontal([
-            Constraint::Length(34),
+            Constraint::Length(tabs_width as u16),
             Constraint::Length(3),
             Constraint::Length((sort_string.len()) as u16 + 2),
         ])
         .flex(Flex::Center)
         .areas(top_bar);
 
-        let listing_type_tabs = Tabs::new().color(CONFIG.general.accent_color);
+        let listing_type_tabs = Tabs::new(tab_lines)
+            .style(Style::default().white())
+            .highlight_style(
+                Style::default()
+                    .fg(CONFIG.general.accent_color)
+                    .not_underlined(),
+            )
+            .select(current_idx)
+            .divider(symbols::DOT);
 
         let separator_paragraph = Paragraph::new(" ⎥ ").bold();
 
         let spans = vec![Line::from(vec![
             Span::from(" "),
-            Span::styled(
-                "4",
-                Style::default()
-                    .underlined()
-                    .underline_color(CONFIG.general.accent_color),
-            ),
+            Span::styled("4", key_style),
             Span::from("."),
             Span::from(sort_string),
             Span::from(""),
@@ -123,20 +340,23 @@ impl Component for TabComponent {
 
         let current_sort_paragraph = Paragraph::new(spans).bg(Color::DarkGray);
 
-        f.render_stateful_widget(listing_type_tabs, listing_type_rect, &mut self.tabs_state);
+        f.render_widget(listing_type_tabs, listing_type_rect);
         f.render_widget(separator_paragraph, separator_rect);
         f.render_widget(current_sort_paragraph, sort_type_rect);
     }
 
     fn handle_actions(&mut self, action: Action) {
-        if let Action::ChangeTab(tab) = action {
-            match tab {
-                1 => self.tabs_state.set(1),
-                2 => self.tabs_state.set(2),
-                3 => self.tabs_state.set(3),
-                _ => (),
-            };
-            self.ctx.send_action(Action::Render);
+        if let Action::ChangeTab(key) = action {
+            let idx = self
+                .tabs
+                .iter()
+                .enumerate()
+                .position(|(idx, tab)| tab.key(idx) == key);
+            if let Some(idx) = idx {
+                self.tabs_state.set(idx + 1);
+                self.picked_tab = true;
+                self.ctx.send_action(Action::Render);
+            }
         }
     }
 }
@@ -1,45 +1,115 @@
 pub mod lemmynator_post;
-mod page;
+pub mod page;
+pub mod saved_comments;
 
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU64, Ordering},
     Arc,
 };
 
 use anyhow::Result;
 use lemmy_api_common::{
-    lemmy_db_schema::{ListingType, SortType},
-    lemmy_db_views::structs::PaginationCursor,
-    post::{GetPosts, GetPostsResponse},
+    lemmy_db_schema::SortType, lemmy_db_views::structs::PaginationCursor, post::GetPosts,
 };
 use ln_config::CONFIG;
 use ratatui::{prelude::*, widgets::Paragraph};
+use tokio_util::sync::CancellationToken;
 
-use self::page::Page;
+use self::{page::Page, saved_comments::SavedComments};
 use super::{centered_rect, components::Component};
 use crate::{
-    action::{Action, UpdateAction},
+    action::{Action, Operation, UpdateAction},
     app::Ctx,
-    types::LemmynatorPost,
+    client::ClientError,
+    types::{Feed, LemmynatorPost, Post},
 };
 
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Tags everything fetched for one incarnation of a paginated view.
+pub fn next_generation() -> u64 {
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Every listing gets its own generation, which tags the pages fetched for it,
+/// so that pages requested by a listing that has since been replaced are never
+/// mistaken for its own. Its requests are cancelled once it's dropped.
 pub struct Listing {
-    listing_type: ListingType,
+    pub generation: u64,
+    cancel: CancellationToken,
+    pub feed: Feed,
     pub sort_type: SortType,
     pub page_data: Page,
     pub can_fetch_new_pages: Arc<AtomicBool>,
+    /// Posts left out so far for matching the filters.
+    filtered: usize,
+    error: Option<ClientError>,
+    /// The posts shown come from the offline cache and are yet to be refreshed.
+    stale: bool,
+    /// Listed below the posts of the Saved tab.
+    saved_comments: Option<SavedComments>,
+    /// Whether the saved comments are in focus rather than the posts.
+    comments_focused: bool,
     ctx: Arc<Ctx>,
 }
 
 impl Listing {
-    pub fn new(listing_type: ListingType, sort_type: SortType, ctx: Arc<Ctx>) -> Result<Self> {
-        Ok(Self {
-            listing_type,
+    pub fn new(feed: Feed, sort_type: SortType, ctx: Arc<Ctx>) -> Result<Self> {
+        let generation = next_generation();
+        let cancel = CancellationToken::new();
+        let saved_comments = (feed == Feed::Saved)
+            .then(|| SavedComments::new(generation, cancel.clone(), Arc::clone(&ctx)));
+
+        let mut listing = Self {
+            generation,
+            cancel,
+            feed,
             sort_type,
             page_data: Page::new(Arc::clone(&ctx)),
             can_fetch_new_pages: Arc::new(AtomicBool::new(true)),
+            filtered: 0,
+            error: None,
+            stale: false,
+            saved_comments,
+            comments_focused: false,
             ctx: Arc::clone(&ctx),
-        })
+        };
+
+        if let Some(cached) = ctx.cache.load_posts(feed, sort_type) {
+            listing.append_posts(cached.posts);
+            listing.stale = true;
+            // There's no cursor yet, so this refreshes the first page.
+            listing.try_fetch_new_pages();
+        }
+
+        Ok(listing)
+    }
+
+    fn append_posts(&mut self, posts: Vec<Post>) {
+        // Not every instance honours `show_read`, and cached pages predate the toggle.
+        let hide_read = self.ctx.hide_read();
+        let show_filtered = self.ctx.show_filtered();
+
+        let mut new_posts = vec![];
+        for post in posts {
+            if hide_read && post.read {
+                continue;
+            }
+            if !show_filtered && self.ctx.filters.hides(&post) {
+                self.filtered += 1;
+                continue;
+            }
+            new_posts.push(LemmynatorPost::from_lemmy_post(post, Arc::clone(&self.ctx)));
+        }
+
+        self.page_data.all_posts_count += new_posts.len();
+        self.page_data.posts.append(&mut new_posts);
+    }
+
+    /// Starts over from the first page, for when what should be shown changed.
+    pub fn reload(&mut self) {
+        *self = Self::new(self.feed, self.sort_type, Arc::clone(&self.ctx)).unwrap();
+        self.ctx.send_action(Action::Render);
     }
 
     pub fn try_fetch_new_pages(&self) {
@@ -49,12 +119,28 @@ impl Listing {
             Ordering::SeqCst,
             Ordering::SeqCst,
         ) {
-            tokio::task::spawn(Self::fetch_next_page(
+            let fetch = tokio::task::spawn(Self::fetch_next_page(
                 self.page_data.next_page.clone(),
                 self.sort_type,
                 Arc::clone(&self.ctx),
-                self.listing_type,
+                self.feed,
+                self.generation,
+                self.cancel.clone(),
             ));
+
+            // Should the fetch die halfway, it never reports back and fetching
+            // would stay blocked forever, so report it like any other failure.
+            let ctx = Arc::clone(&self.ctx);
+            let operation = Operation::FetchPage(self.feed, self.generation);
+            tokio::task::spawn(async move {
+                if let Err(e) = fetch.await {
+                    ctx.send_update_action(UpdateAction::Error(
+                        operation,
+                        ClientError::Other(format!("fetching posts crashed: {e}")),
+                    ));
+                    ctx.send_action(Action::Render);
+                }
+            });
         }
     }
 
@@ -62,29 +148,122 @@ impl Listing {
         page_cursor: Option<PaginationCursor>,
         sort_type: SortType,
         ctx: Arc<Ctx>,
-        listing_type: ListingType,
+        feed: Feed,
+        generation: u64,
+        cancel: CancellationToken,
     ) {
-        let posts_req = GetPosts {
-            type_: Some(listing_type),
+        let mut posts_req = GetPosts {
             sort: Some(sort_type),
             page_cursor,
             limit: Some(20),
+            show_read: Some(!ctx.hide_read()),
             ..Default::default()
         };
+        match feed {
+            Feed::Listing(listing_type) => posts_req.type_ = Some(listing_type),
+            Feed::Community(community_id) => posts_req.community_id = Some(community_id),
+            Feed::Saved => posts_req.saved_only = Some(true),
+        }
+
+        // Dropping the request aborts it, nobody is waiting for it anymore.
+        let res = tokio::select! {
+            _ = cancel.cancelled() => return,
+            res = ctx.api.get_posts(&posts_req) => res,
+        };
+
+        match res {
+            Ok(new_page) => {
+                ctx.send_update_action(UpdateAction::NewPage(feed, generation, new_page))
+            }
+            Err(e) => ctx.send_update_action(UpdateAction::Error(
+                Operation::FetchPage(feed, generation),
+                e,
+            )),
+        }
+
+        ctx.send_action(Action::Render);
+    }
+
+    /// The saved comments, when they're there and take the actions, either
+    /// because they're in focus or because there are no posts.
+    fn active_saved_comments(&mut self) -> Option<&mut SavedComments> {
+        let posts_empty = self.page_data.posts.is_empty();
+        let comments_focused = self.comments_focused;
+        self.saved_comments
+            .as_mut()
+            .filter(|comments| !comments.is_empty() && (comments_focused || posts_empty))
+    }
 
-        let req = ctx
-            .client
-            .get(format!(
-                "https://{}/api/v3/post/list",
-                CONFIG.connection.instance
-            ))
-            .query(&posts_req);
+    /// Fetches the saved comments again after a failed request.
+    pub fn retry_saved_comments(&mut self) {
+        if let Some(saved_comments) = &mut self.saved_comments {
+            saved_comments.retry();
+        }
+    }
+
+    /// Lets the listing fetch again after a failed request.
+    pub fn retry(&mut self) {
+        self.error = None;
+        self.can_fetch_new_pages.store(true, Ordering::SeqCst);
+        self.ctx.send_action(Action::Render);
+    }
 
-        let new_page: GetPostsResponse = req.send().await.unwrap().json().await.unwrap();
+    fn render_error_screen(&mut self, f: &mut Frame, rect: Rect, error: &ClientError) {
+        let error_rect = centered_rect(rect, 80, 10);
+        let error_paragraph = Paragraph::new(vec![
+            Line::styled(format!("Could not load posts: {error}"), Style::new().red()),
+            Line::from(vec![
+                Span::raw("Press "),
+                Span::styled(
+                    "r",
+                    Style::default()
+                        .underlined()
+                        .fg(CONFIG.general.accent_color),
+                ),
+                Span::raw(" to try again."),
+            ]),
+        ])
+        .alignment(Alignment::Center);
+        f.render_widget(error_paragraph, error_rect);
+    }
 
-        ctx.send_update_action(UpdateAction::NewPage(listing_type, sort_type, new_page));
+    /// Tells what is being left out and how to bring it back.
+    fn render_status(&self, f: &mut Frame, rect: Rect) {
+        let key_style = Style::default()
+            .underlined()
+            .fg(CONFIG.general.accent_color);
 
-        ctx.action_tx.send(Action::Render).unwrap();
+        let mut notes = vec![];
+        if self.ctx.show_filtered() {
+            if !self.ctx.filters.is_empty() {
+                notes.push(vec![
+                    Span::raw("Filters off, "),
+                    Span::styled("F", key_style),
+                    Span::raw(" to apply them"),
+                ]);
+            }
+        } else if self.filtered > 0 {
+            notes.push(vec![
+                Span::raw(format!("{} filtered, ", self.filtered)),
+                Span::styled("F", key_style),
+                Span::raw(" to show them"),
+            ]);
+        }
+        if self.ctx.hide_read() {
+            notes.push(vec![
+                Span::raw("Read posts hidden, "),
+                Span::styled("R", key_style),
+                Span::raw(" to show them"),
+            ]);
+        }
+
+        if notes.is_empty() {
+            return;
+        }
+        let mut spans = notes.join(&Span::raw(" · "));
+        spans.push(Span::raw(" "));
+        let status = Paragraph::new(Line::from(spans).dim()).right_aligned();
+        f.render_widget(status, rect);
     }
 
     // TODO: make this into a component
@@ -95,35 +274,93 @@ impl Listing {
     }
 }
 
+impl Drop for Listing {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
 impl Component for Listing {
     fn handle_actions(&mut self, action: Action) {
-        self.page_data.handle_actions(action);
+        match action {
+            Action::Retry if self.error.is_some() => self.retry(),
+            Action::ChangeFocus
+                if self
+                    .saved_comments
+                    .as_ref()
+                    .is_some_and(|comments| !comments.is_empty()) =>
+            {
+                self.comments_focused = !self.comments_focused;
+                self.ctx.send_action(Action::Render);
+            }
+            _ if self.active_saved_comments().is_some() => {
+                if let Some(saved_comments) = self.active_saved_comments() {
+                    saved_comments.handle_actions(action);
+                }
+            }
+            _ if self.page_data.posts.is_empty() => (),
+            _ => self.page_data.handle_actions(action),
+        }
     }
 
     fn handle_update_action(&mut self, action: UpdateAction) {
         match action {
-            UpdateAction::NewPage(_, sort_type, new_page) => {
-                if self.sort_type == sort_type {
-                    let mut new_posts: Vec<LemmynatorPost> = new_page
-                        .posts
-                        .into_iter()
-                        .map(|post| LemmynatorPost::from_lemmy_post(post, self.ctx.clone()))
-                        .collect();
-
-                    self.page_data.all_posts_count += new_posts.len();
-                    self.page_data.posts.append(&mut new_posts);
+            UpdateAction::NewPage(_, generation, new_page) => {
+                if self.generation == generation {
+                    let is_first_page = self.stale || self.page_data.posts.is_empty();
+                    self.ctx
+                        .cache
+                        .store_posts(self.feed, self.sort_type, is_first_page, &new_page);
+
+                    if self.stale {
+                        self.page_data = Page::new(Arc::clone(&self.ctx));
+                        self.filtered = 0;
+                        self.stale = false;
+                    }
+
+                    self.append_posts(new_page.posts);
                     self.page_data.next_page = new_page.next_page;
                     self.can_fetch_new_pages.store(true, Ordering::SeqCst);
                 }
             }
+            UpdateAction::SavedComments(generation, _) if self.generation == generation => {
+                if let Some(saved_comments) = &mut self.saved_comments {
+                    saved_comments.handle_update_action(action);
+                }
+            }
+            UpdateAction::SavedComments(_, _)
+            | UpdateAction::Error(Operation::FetchSavedComments(_), _) => (),
+            UpdateAction::Error(Operation::FetchPage(_, generation), error) => {
+                // Fetching stays blocked until the user explicitly retries, so a
+                // dead instance does not get hammered on every render.
+                if self.generation == generation {
+                    self.error = Some(error);
+                }
+            }
             _ => unreachable!(),
         }
     }
 
     fn render(&mut self, f: &mut Frame, rect: Rect) {
-        let [posts_rect, bottom_bar_rect] =
+        let [mut posts_rect, bottom_bar_rect] =
             Layout::vertical([Constraint::Percentage(100), Constraint::Length(1)]).areas(rect);
 
+        if let Some(saved_comments) = &mut self.saved_comments {
+            saved_comments.load();
+        }
+        if let Some(saved_comments) = self
+            .saved_comments
+            .as_mut()
+            .filter(|comments| !comments.is_empty())
+        {
+            let comments_rect;
+            [posts_rect, comments_rect] =
+                Layout::vertical([Constraint::Percentage(65), Constraint::Percentage(35)])
+                    .areas(posts_rect);
+            let is_focused = self.comments_focused || self.page_data.posts.is_empty();
+            saved_comments.render_titled(f, comments_rect, is_focused);
+        }
+
         let mut are_there_pages_available;
 
         {
@@ -147,11 +384,15 @@ impl Component for Listing {
 
         if are_there_pages_available {
             self.page_data.render(f, posts_rect)
+        } else if let Some(error) = self.error.clone() {
+            self.render_error_screen(f, posts_rect, &error);
         } else {
             self.try_fetch_new_pages();
             self.render_loading_screen(f, posts_rect);
         }
 
         self.page_data.render_bottom_bar(f, bottom_bar_rect);
+
+        self.render_status(f, bottom_bar_rect);
     }
 }
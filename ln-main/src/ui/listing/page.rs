@@ -38,8 +38,14 @@ impl Page {
         &mut self.posts[self.posts_offset + self.currently_focused as usize]
     }
 
-    fn current_post(&mut self) -> &LemmynatorPost {
-        &self.posts[self.posts_offset + self.currently_focused as usize]
+    pub fn focused_post(&self) -> Option<&LemmynatorPost> {
+        self.posts
+            .get(self.posts_offset + self.currently_focused as usize)
+    }
+
+    /// Whether the post in focus is the last one there is, so far.
+    pub fn is_last_focused(&self) -> bool {
+        self.posts_offset + self.currently_focused as usize + 1 >= self.posts.len()
     }
 
     fn scroll_up(&mut self) {
@@ -52,6 +58,9 @@ impl Page {
     }
 
     fn scroll_down(&mut self) {
+        if CONFIG.feed.mark_read_on_scroll {
+            self.current_post_mut().mark_read();
+        }
         self.currently_focused += 1;
         if self.currently_focused >= self.currently_displaying {
             self.posts_offset += self.currently_displaying as usize;
@@ -65,7 +74,9 @@ impl Page {
 
     fn rects_for_posts(&mut self, mut rect_pool: Rect) -> Vec<Rect> {
         let offseted_posts = &mut self.posts[self.posts_offset..];
-        let posts = &mut offseted_posts[..self.currently_displaying as usize];
+        // Only search results end on a partial page, listings keep fetching.
+        let shown = offseted_posts.len().min(self.currently_displaying as usize);
+        let posts = &mut offseted_posts[..shown];
 
         let mut rects = vec![];
         for post in posts {
@@ -102,11 +113,11 @@ impl Page {
         space_for_padding_available: bool,
     ) {
         let offseted_posts = &mut self.posts[self.posts_offset..];
-        let posts = &mut offseted_posts[..self.currently_displaying as usize];
+        let shown = offseted_posts.len().min(self.currently_displaying as usize);
+        let posts = &mut offseted_posts[..shown];
 
         let mut current_offset = 0;
-        let mut index = 0;
-        for (post, rect) in posts.iter_mut().zip(rects.into_iter()) {
+        for (index, (post, rect)) in posts.iter_mut().zip(rects.iter_mut()).enumerate() {
             if space_for_padding_available {
                 current_offset += 1;
                 rect.y += current_offset;
@@ -118,8 +129,11 @@ impl Page {
 
             post.render(f, *rect);
 
+            if post.read && !post.is_focused {
+                f.buffer_mut().set_style(*rect, Style::new().dim());
+            }
+
             post.is_focused = false;
-            index += 1;
         }
     }
 
@@ -152,9 +166,13 @@ impl Page {
             let current_page_paragraph =
                 Paragraph::new(format!("{} / ", self.current_page())).alignment(Alignment::Center);
             f.render_widget(current_page_paragraph, rect);
-            let keytip_paragraph = Paragraph::new(Line::from(spans)).left_aligned();
-            rect.x = 0;
-            f.render_widget(keytip_paragraph, rect);
+
+            // Guests can't vote, so there's nothing to hint at.
+            if !self.ctx.is_anonymous() {
+                let keytip_paragraph = Paragraph::new(Line::from(spans)).left_aligned();
+                rect.x = 0;
+                f.render_widget(keytip_paragraph, rect);
+            }
         }
     }
 }
@@ -171,8 +189,13 @@ impl Component for Page {
                 self.ctx.send_action(Action::Render);
             }
             Action::Confirm => {
-                let post = self.current_post().clone();
-                self.ctx.send_update_action(UpdateAction::ViewPost(post));
+                let post = self.current_post_mut();
+                post.mark_read();
+                // Opening the post shows all of its comments.
+                post.unread_comments = 0;
+                let post = post.clone();
+                self.ctx
+                    .send_update_action(UpdateAction::ViewPost(Box::new(post)));
             }
             _ => self.current_post_mut().handle_actions(action),
         }
@@ -190,10 +213,7 @@ impl Component for Page {
         }
 
         let mut rects = self.rects_for_posts(rect);
-        let size_occupied = rects
-            .iter()
-            .map(|rect| rect.height)
-            .fold(0, |acc, height| acc + height);
+        let size_occupied = rects.iter().map(|rect| rect.height).sum::<u16>();
 
         let is_space_for_padding_available =
             main_rect.height - size_occupied > self.currently_displaying as u16;
@@ -0,0 +1,181 @@
+use std::sync::Arc;
+
+use lemmy_api_common::{
+    comment::GetComments,
+    lemmy_db_schema::{CommentSortType, ListingType},
+};
+use ln_config::CONFIG;
+use ratatui::{prelude::*, widgets::Paragraph};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    action::{Action, Operation, UpdateAction},
+    app::Ctx,
+    types::Comment,
+    ui::components::{
+        entry_list::{Entry, EntryList},
+        Component,
+    },
+};
+
+/// How many saved comments are fetched at once.
+const PAGE_SIZE: i64 = 20;
+
+/// The comments the account saved, shown below the saved posts.
+pub struct SavedComments {
+    pub list: EntryList,
+    /// The next page to fetch, `None` once every comment is there.
+    next_page: Option<i64>,
+    /// Whether a page is on its way, or failed and waits for a retry.
+    is_fetching: bool,
+    generation: u64,
+    cancel: CancellationToken,
+    ctx: Arc<Ctx>,
+}
+
+impl SavedComments {
+    /// Fetches pages tagged with the generation of the listing showing them,
+    /// cancelled along with its requests.
+    pub fn new(generation: u64, cancel: CancellationToken, ctx: Arc<Ctx>) -> Self {
+        Self {
+            list: EntryList::new(vec![], false),
+            next_page: Some(1),
+            is_fetching: false,
+            generation,
+            cancel,
+            ctx,
+        }
+    }
+
+    /// Fetches the first page, once the tab is actually looked at.
+    pub fn load(&mut self) {
+        if self.next_page == Some(1) {
+            self.fetch_next_page();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    fn fetch_next_page(&mut self) {
+        let Some(page) = self.next_page else {
+            return;
+        };
+        if self.is_fetching {
+            return;
+        }
+        self.is_fetching = true;
+
+        let req = GetComments {
+            type_: Some(ListingType::All),
+            sort: Some(CommentSortType::New),
+            page: Some(page),
+            limit: Some(PAGE_SIZE),
+            saved_only: Some(true),
+            ..Default::default()
+        };
+        let generation = self.generation;
+        let cancel = self.cancel.clone();
+        let ctx = Arc::clone(&self.ctx);
+        tokio::task::spawn(async move {
+            let res = tokio::select! {
+                _ = cancel.cancelled() => return,
+                res = ctx.api.get_comments(&req) => res,
+            };
+
+            match res {
+                Ok(res) => ctx.send_update_action(UpdateAction::SavedComments(generation, res)),
+                Err(e) => ctx.send_update_action(UpdateAction::Error(
+                    Operation::FetchSavedComments(generation),
+                    e,
+                )),
+            }
+            ctx.send_action(Action::Render);
+        });
+    }
+
+    /// Fetches again after a failed request.
+    pub fn retry(&mut self) {
+        self.is_fetching = false;
+        self.fetch_next_page();
+    }
+
+    fn append(&mut self, comments: Vec<Comment>) {
+        let count = comments.len() as i64;
+        // Instances on Lemmy 1.0 ignore the page and send the first one again,
+        // which then ends the list.
+        let before = self.list.entries.len();
+        for comment in comments {
+            let is_new = !self
+                .list
+                .entries
+                .iter()
+                .any(|entry| matches!(entry, Entry::Comment(saved) if saved.id == comment.id));
+            if is_new {
+                self.list.entries.push(Entry::Comment(Box::new(comment)));
+            }
+        }
+        let added = self.list.entries.len() > before;
+
+        self.next_page = self
+            .next_page
+            .filter(|_| count == PAGE_SIZE && added)
+            .map(|page| page + 1);
+        self.is_fetching = false;
+    }
+
+    pub fn render_titled(&mut self, f: &mut Frame, rect: Rect, is_focused: bool) {
+        let [title_rect, list_rect] =
+            Layout::vertical([Constraint::Length(2), Constraint::Fill(1)]).areas(rect);
+
+        let title_style = if is_focused {
+            Style::default().fg(CONFIG.general.accent_color).bold()
+        } else {
+            Style::default().bold()
+        };
+        let key_style = Style::default()
+            .underlined()
+            .fg(CONFIG.general.accent_color);
+        let title = Line::from(vec![
+            Span::styled(" Saved comments ", title_style),
+            Span::raw("· "),
+            Span::styled("Tab", key_style),
+            Span::raw(" to switch"),
+        ]);
+        f.render_widget(Paragraph::new(title), title_rect);
+
+        self.render(f, list_rect);
+    }
+}
+
+impl Component for SavedComments {
+    fn handle_actions(&mut self, action: Action) {
+        match action {
+            Action::Confirm => {
+                if let Some(entry) = self.list.focused() {
+                    entry.open(&self.ctx);
+                }
+            }
+            Action::Up | Action::Down => {
+                self.list.handle_actions(action);
+                if self.list.remaining() < PAGE_SIZE as usize / 2 {
+                    self.fetch_next_page();
+                }
+                self.ctx.send_action(Action::Render);
+            }
+            _ => (),
+        }
+    }
+
+    fn handle_update_action(&mut self, action: UpdateAction) {
+        // Failures leave fetching blocked until the user retries.
+        if let UpdateAction::SavedComments(_, comments) = action {
+            self.append(comments);
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        self.list.render(f, rect);
+    }
+}
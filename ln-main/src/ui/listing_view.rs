@@ -1,15 +1,18 @@
-use std::{collections::HashMap, sync::Arc, time::Duration};
-
-use lemmy_api_common::{lemmy_db_schema::SortType, person::GetUnreadCountResponse};
-use ln_config::CONFIG;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
 use crate::{
-    action::{Action, UpdateAction},
+    action::{Action, Operation, UpdateAction},
     app::{self, Ctx},
 };
 
 use super::{
-    components::{tabs::CurrentTab, Component},
+    components::{
+        tabs::{CurrentTab, TabComponent},
+        Component,
+    },
     listing::Listing,
 };
 
@@ -19,25 +22,7 @@ pub struct ListingView {
 }
 
 impl ListingView {
-    pub async fn new(ctx: Arc<app::Ctx>) -> Self {
-        let _ctx = Arc::clone(&ctx);
-
-        tokio::spawn(async move {
-            tokio::time::sleep(Duration::from_secs(2)).await;
-            let unread_counts: GetUnreadCountResponse = _ctx
-                .client
-                .get(format!(
-                    "https://{}/api/v3/user/unread_count",
-                    CONFIG.connection.instance
-                ))
-                .send()
-                .await
-                .unwrap()
-                .json()
-                .await
-                .unwrap();
-            _ctx.send_update_action(UpdateAction::UpdateUnreadsCount(unread_counts));
-        });
+    pub fn new(ctx: Arc<app::Ctx>) -> Self {
         let mut listing_view = Self {
             listings: HashMap::new(),
             ctx,
@@ -49,14 +34,23 @@ impl ListingView {
     }
 
     fn populate_listings(&mut self) {
-        let default_sort_type = SortType::Hot;
-
-        for tab in [CurrentTab::Subscribed, CurrentTab::Local, CurrentTab::All] {
+        for tab in TabComponent::available_tabs(&self.ctx) {
             let ctx = Arc::clone(&self.ctx);
-            let listing = Listing::new(tab.as_listing_type(), default_sort_type, ctx).unwrap();
+            let listing = Listing::new(tab.feed(), tab.default_sort(&self.ctx), ctx).unwrap();
             self.listings.insert(tab, listing);
         }
     }
+
+    /// Tabs whose listing was scrolled away from its first post.
+    pub fn scrolled_tabs(&self) -> HashSet<CurrentTab> {
+        self.listings
+            .iter()
+            .filter(|(_, listing)| {
+                listing.page_data.posts_offset != 0 || listing.page_data.currently_focused != 0
+            })
+            .map(|(tab, _)| *tab)
+            .collect()
+    }
 }
 
 impl Component for ListingView {
@@ -64,11 +58,19 @@ impl Component for ListingView {
 
     fn handle_update_action(&mut self, action: crate::action::UpdateAction) {
         match action {
-            UpdateAction::NewPage(listing_type, _, _) => {
-                self.listings
-                    .get_mut(&listing_type.into())
-                    .expect("Listing already populated")
-                    .handle_update_action(action);
+            UpdateAction::SavedComments(_, _)
+            | UpdateAction::Error(Operation::FetchSavedComments(_), _) => {
+                if let Some(listing) = self.listings.get_mut(&CurrentTab::Saved) {
+                    listing.handle_update_action(action);
+                }
+            }
+            UpdateAction::NewPage(feed, _, _)
+            | UpdateAction::Error(Operation::FetchPage(feed, _), _) => {
+                if let Some(listing) =
+                    CurrentTab::from_feed(feed).and_then(|tab| self.listings.get_mut(&tab))
+                {
+                    listing.handle_update_action(action);
+                }
             }
             _ => (),
         }
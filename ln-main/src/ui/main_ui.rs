@@ -1,19 +1,37 @@
 use std::sync::Arc;
 
 use crate::{
-    action::{Action, UpdateAction},
+    action::{Action, Operation, UpdateAction},
     app::Ctx,
+    types::{self, CommunityRef, Feed, LemmynatorPost, LemmynatorPostComments},
+    unreads,
 };
 
 use super::{
-    components::Component, listing::Listing, listing_view::ListingView, post_view::PostView,
+    community_browser::CommunityBrowser,
+    community_view::{self, CommunityView},
+    components::{
+        entry_list,
+        input::{InputOutcome, InputPopup},
+        picker::{Picker, PickerOutcome},
+        tabs::{sort_name, top_window, CurrentTab, SORT_TYPES},
+        Component,
+    },
+    listing::Listing,
+    listing_view::ListingView,
+    notifications::Notifications,
+    post_view::PostView,
+    profile_view::{self, ProfileView},
+    search_view::SearchView,
     top_bar::TopBar,
+    totp_prompt::TotpPrompt,
 };
 
 use anyhow::Result;
 use lemmy_api_common::{
-    comment::{GetComments, GetCommentsResponse},
-    person::GetUnreadCountResponse,
+    comment::GetComments,
+    lemmy_db_schema::{newtypes::PersonId, SortType},
+    post::CreatePostLike,
 };
 use ln_config::CONFIG;
 use ratatui::prelude::*;
@@ -22,23 +40,43 @@ pub struct MainWindow {
     top_bar: TopBar,
     listing_view: ListingView,
     post_view: Option<PostView>,
+    profile_view: Option<ProfileView>,
+    community_view: Option<CommunityView>,
+    community_browser: Option<CommunityBrowser>,
+    search_view: Option<SearchView>,
+    community_prompt: Option<InputPopup>,
+    /// Writing a private message to someone.
+    message_prompt: Option<(PersonId, InputPopup)>,
+    notifications: Notifications,
+    totp_prompt: Option<TotpPrompt>,
+    profile_picker: Option<Picker>,
+    logout_prompt: Option<Picker>,
+    sort_picker: Option<Picker>,
     ctx: Arc<Ctx>,
 }
 
 impl MainWindow {
     pub async fn new(ctx: Arc<Ctx>) -> Result<Self> {
+        unreads::spawn_poller(Arc::clone(&ctx));
+
         Ok(Self {
-            top_bar: TopBar::new(
-                Arc::clone(&ctx),
-                GetUnreadCountResponse {
-                    replies: 0,
-                    mentions: 0,
-                    private_messages: 0,
-                },
-            )
-            .await,
-            listing_view: ListingView::new(Arc::clone(&ctx)).await,
+            top_bar: TopBar::new(Arc::clone(&ctx), 0).await,
+            listing_view: ListingView::new(Arc::clone(&ctx)),
             post_view: None,
+            profile_view: None,
+            community_view: None,
+            community_browser: None,
+            search_view: None,
+            community_prompt: None,
+            message_prompt: None,
+            notifications: Notifications::default(),
+            totp_prompt: ctx
+                .api
+                .needs_totp()
+                .then(|| TotpPrompt::new(Arc::clone(&ctx), true)),
+            profile_picker: None,
+            logout_prompt: None,
+            sort_picker: None,
             ctx,
         })
     }
@@ -50,10 +88,41 @@ impl MainWindow {
             .expect("Listings already populated")
     }
 
-    fn change_sort(&mut self) {
-        self.top_bar.tabs.change_sort();
+    /// The sort of whatever is shown, be it a community, a search, the community browser or a tab.
+    fn current_sort(&self) -> SortType {
+        if let Some(community_view) = &self.community_view {
+            return community_view.sort_type();
+        }
+        if let Some(search_view) = &self.search_view {
+            return search_view.sort_type();
+        }
+        match &self.community_browser {
+            Some(community_browser) => community_browser.sort_type(),
+            None => self.top_bar.tabs.current_sort(),
+        }
+    }
+
+    fn change_sort(&mut self, sort: SortType) {
+        if sort == self.current_sort() {
+            return;
+        }
+
+        if let Some(community_view) = &mut self.community_view {
+            community_view.set_sort(sort);
+            return;
+        }
+        if let Some(search_view) = &mut self.search_view {
+            search_view.set_sort(sort);
+            return;
+        }
+        if let Some(community_browser) = &mut self.community_browser {
+            community_browser.set_sort(sort);
+            return;
+        }
+
+        self.top_bar.tabs.set_sort(sort);
         let new_listing = Listing::new(
-            self.top_bar.tabs.current_listing_type(),
+            self.top_bar.tabs.current_feed(),
             self.top_bar.tabs.current_sort(),
             Arc::clone(&self.ctx),
         )
@@ -65,11 +134,550 @@ impl MainWindow {
 
         self.ctx.send_action(Action::Render);
     }
+
+    /// Loads the instance's details once startup went on without them.
+    fn load_site(&self) {
+        let ctx = Arc::clone(&self.ctx);
+        tokio::task::spawn(async move {
+            if ctx.load_site().await {
+                ctx.send_update_action(UpdateAction::SiteLoaded);
+            }
+            ctx.send_action(Action::Render);
+        });
+    }
+
+    /// Applies the instance's and the account's default listing and sort now
+    /// that they're known, leaving alone whatever the user already picked or
+    /// scrolled through, and adds the tabs a freshly logged in account has.
+    fn apply_site_defaults(&mut self) {
+        self.top_bar.load_icon();
+
+        let scrolled = self.listing_view.scrolled_tabs();
+        for tab in self.top_bar.tabs.apply_site_defaults(&scrolled) {
+            let listing = Listing::new(
+                tab.feed(),
+                self.top_bar.tabs.sort(tab),
+                Arc::clone(&self.ctx),
+            )
+            .unwrap();
+            self.listing_view.listings.insert(tab, listing);
+        }
+        self.ctx.send_action(Action::Render);
+    }
+
+    /// Reloads every listing, as what they should leave out changed.
+    fn reload_listings(&mut self) {
+        for listing in self.listing_view.listings.values_mut() {
+            listing.reload();
+        }
+        if let Some(listing) = self
+            .community_view
+            .as_mut()
+            .and_then(|community_view| community_view.listing.as_mut())
+        {
+            listing.reload();
+        }
+    }
+
+    fn fetch_comments(&self, post: &LemmynatorPost) {
+        let params = GetComments {
+            community_id: Some(post.community_id),
+            post_id: Some(post.id),
+            max_depth: Some(8),
+            limit: Some(100),
+            ..Default::default()
+        };
+
+        let ctx = Arc::clone(&self.ctx);
+        let post_id = post.id;
+        tokio::task::spawn(async move {
+            // Show what we've got right away, the fresh comments replace it once they arrive.
+            if let Some(cached) = ctx.cache.load_comments(post_id) {
+                ctx.send_update_action(UpdateAction::CommentsForPost(post_id, cached));
+            }
+
+            match ctx.api.get_comments(&params).await {
+                Ok(comments) => {
+                    ctx.cache.store_comments(post_id, &comments);
+                    ctx.send_update_action(UpdateAction::CommentsForPost(post_id, comments));
+                }
+                Err(e) => ctx
+                    .send_update_action(UpdateAction::Error(Operation::FetchComments(post_id), e)),
+            }
+        });
+    }
+
+    fn open_profile_picker(&mut self) {
+        let mut current = 0;
+        let profiles = CONFIG
+            .profiles()
+            .enumerate()
+            .map(|(idx, profile)| {
+                if std::ptr::eq(profile, self.ctx.profile) {
+                    current = idx;
+                }
+                let account = profile.username.as_deref().unwrap_or("guest");
+                Line::from(vec![
+                    Span::raw(format!("{} ", profile.name())),
+                    Span::raw(format!("{account}@{}", profile.instance)).dim(),
+                ])
+            })
+            .collect();
+
+        self.profile_picker = Some(Picker::new("Switch account", profiles, current));
+        self.ctx.send_action(Action::Render);
+    }
+
+    fn handle_profile_picker_action(&mut self, action: Action) {
+        let Some(profile_picker) = &mut self.profile_picker else {
+            return;
+        };
+
+        match profile_picker.handle_action(&action) {
+            PickerOutcome::Picked(idx) => {
+                self.profile_picker = None;
+                self.ctx.send_action(Action::SwitchProfile(idx));
+            }
+            PickerOutcome::Cancelled => self.profile_picker = None,
+            PickerOutcome::Pending => (),
+        }
+        self.ctx.send_action(Action::Render);
+    }
+
+    /// Logging out is one key away from voting, so it takes a confirmation.
+    fn open_logout_prompt(&mut self) {
+        if self.ctx.is_anonymous() {
+            return;
+        }
+        let choices = vec![Line::raw("Stay logged in"), Line::raw("Log out and quit")];
+        self.logout_prompt =
+            Some(Picker::new("Log out?", choices, 0).with_keys(vec![Some('n'), Some('y')]));
+        self.ctx.send_action(Action::Render);
+    }
+
+    fn handle_logout_prompt_action(&mut self, action: Action) {
+        let Some(logout_prompt) = &mut self.logout_prompt else {
+            return;
+        };
+
+        match logout_prompt.handle_action(&action) {
+            PickerOutcome::Picked(1) => {
+                self.logout_prompt = None;
+                self.ctx.send_action(Action::ConfirmLogout);
+            }
+            PickerOutcome::Picked(_) | PickerOutcome::Cancelled => self.logout_prompt = None,
+            PickerOutcome::Pending => (),
+        }
+        self.ctx.send_action(Action::Render);
+    }
+
+    fn open_sort_picker(&mut self) {
+        let current_sort = self.current_sort();
+        let current = SORT_TYPES
+            .iter()
+            .position(|(sort, _)| *sort == current_sort)
+            .unwrap_or_default();
+
+        let (sorts, keys) = SORT_TYPES
+            .iter()
+            .map(|(sort, key)| {
+                // Keeps the many `Top` windows visibly together.
+                let line = match top_window(*sort) {
+                    Some(window) => Line::from(vec![Span::raw("Top ").dim(), Span::raw(window)]),
+                    None => Line::from(sort_name(*sort)),
+                };
+                (line, Some(*key))
+            })
+            .unzip();
+
+        self.sort_picker = Some(Picker::new("Sort by", sorts, current).with_keys(keys));
+        self.ctx.send_action(Action::Render);
+    }
+
+    fn handle_sort_picker_action(&mut self, action: Action) {
+        let Some(sort_picker) = &mut self.sort_picker else {
+            return;
+        };
+
+        match sort_picker.handle_action(&action) {
+            PickerOutcome::Picked(idx) => {
+                self.sort_picker = None;
+                self.change_sort(SORT_TYPES[idx].0);
+            }
+            PickerOutcome::Cancelled => self.sort_picker = None,
+            PickerOutcome::Pending => (),
+        }
+        self.ctx.send_action(Action::Render);
+    }
+
+    fn open_community(&mut self, target: CommunityRef) {
+        let sort_type = self.ctx.site().default_sort_type;
+        self.post_view = None;
+        self.profile_view = None;
+        self.community_view = Some(CommunityView::new(target, sort_type, Arc::clone(&self.ctx)));
+        self.ctx.send_action(Action::Render);
+    }
+
+    /// Opens the community of the post that's open or in focus.
+    fn open_focused_community(&mut self) {
+        let community_id = match (&self.post_view, &self.community_view) {
+            (Some(post_view), _) => Some(post_view.post.community_id),
+            (None, _) if self.profile_view.is_some() => self
+                .profile_view
+                .as_ref()
+                .and_then(ProfileView::focused_community_id),
+            // Every post in there is from the community that's already open.
+            (None, Some(_)) => None,
+            (None, None) if self.search_view.is_some() => self
+                .search_view
+                .as_ref()
+                .and_then(SearchView::focused_community_id),
+            (None, None) if self.community_browser.is_some() => self
+                .community_browser
+                .as_ref()
+                .and_then(CommunityBrowser::focused_community_id),
+            (None, None) => self
+                .get_current_listing()
+                .page_data
+                .focused_post()
+                .map(|post| post.community_id),
+        };
+
+        if let Some(community_id) = community_id {
+            self.open_community(CommunityRef::Id(community_id));
+        }
+    }
+
+    fn open_person(&mut self, person_id: PersonId) {
+        self.post_view = None;
+        self.profile_view = Some(ProfileView::new(person_id, Arc::clone(&self.ctx)));
+        self.ctx.send_action(Action::Render);
+    }
+
+    /// Opens the profile of whoever wrote the post that's open or in focus.
+    fn open_focused_author(&mut self) {
+        let person_id = if let Some(post_view) = &self.post_view {
+            Some(post_view.post.creator_id)
+        } else if self.profile_view.is_some() {
+            // Everything in there is by the person that's already open.
+            None
+        } else if let Some(community_view) = &self.community_view {
+            community_view
+                .listing
+                .as_ref()
+                .and_then(|listing| listing.page_data.focused_post())
+                .map(|post| post.creator_id)
+        } else if let Some(search_view) = &self.search_view {
+            search_view.focused_person_id()
+        } else if self.community_browser.is_some() {
+            None
+        } else {
+            self.get_current_listing()
+                .page_data
+                .focused_post()
+                .map(|post| post.creator_id)
+        };
+
+        if let Some(person_id) = person_id {
+            self.open_person(person_id);
+        }
+    }
+
+    fn open_message_prompt(&mut self) {
+        let Some(profile_view) = &self.profile_view else {
+            return;
+        };
+        if self.ctx.is_anonymous() || self.ctx.site().person_id == Some(profile_view.person_id()) {
+            return;
+        }
+
+        let title = match profile_view.name() {
+            Some(name) => format!("Message u/{name}"),
+            None => "Message".to_string(),
+        };
+        self.message_prompt = Some((profile_view.person_id(), InputPopup::new(title)));
+        self.ctx.send_action(Action::SwitchToInputMode);
+    }
+
+    fn handle_message_prompt_action(&mut self, action: Action) {
+        let (Some((recipient_id, message_prompt)), Action::Input(key)) =
+            (&mut self.message_prompt, action)
+        else {
+            return;
+        };
+
+        match message_prompt.handle_key(key) {
+            InputOutcome::Submitted(content) => {
+                tokio::task::spawn(profile_view::send_message(
+                    Arc::clone(&self.ctx),
+                    *recipient_id,
+                    content,
+                ));
+                self.message_prompt = None;
+                self.ctx.send_action(Action::SwitchToNormalMode);
+            }
+            InputOutcome::Cancelled => {
+                self.message_prompt = None;
+                self.ctx.send_action(Action::SwitchToNormalMode);
+            }
+            InputOutcome::Pending => (),
+        }
+        self.ctx.send_action(Action::Render);
+    }
+
+    fn open_community_prompt(&mut self) {
+        let mut input = InputPopup::new("Go to community");
+        input.set_message(Some(Line::raw("Like rust or rust@programming.dev").dim()));
+        self.community_prompt = Some(input);
+        self.ctx.send_action(Action::SwitchToInputMode);
+    }
+
+    fn handle_community_prompt_action(&mut self, action: Action) {
+        let (Some(community_prompt), Action::Input(key)) = (&mut self.community_prompt, action)
+        else {
+            return;
+        };
+
+        match community_prompt.handle_key(key) {
+            InputOutcome::Submitted(name) => {
+                self.community_prompt = None;
+                self.ctx.send_action(Action::SwitchToNormalMode);
+                // Accept the ways communities are usually written down as well.
+                let name = name.trim_start_matches('!').trim_start_matches("c/");
+                self.open_community(CommunityRef::Name(name.to_string()));
+            }
+            InputOutcome::Cancelled => {
+                self.community_prompt = None;
+                self.ctx.send_action(Action::SwitchToNormalMode);
+            }
+            InputOutcome::Pending => (),
+        }
+        self.ctx.send_action(Action::Render);
+    }
+
+    fn open_search(&mut self) {
+        self.post_view = None;
+        self.profile_view = None;
+        self.community_view = None;
+        self.community_browser = None;
+        self.search_view = Some(SearchView::new(
+            self.top_bar.tabs.current_listing_type(),
+            Arc::clone(&self.ctx),
+        ));
+        self.ctx.send_action(Action::Render);
+    }
+
+    fn handle_search_input(&mut self, action: Action) {
+        let Some(search_view) = &mut self.search_view else {
+            return;
+        };
+
+        search_view.handle_actions(action);
+        if search_view.is_abandoned() {
+            self.search_view = None;
+            self.ctx.send_action(Action::Render);
+        }
+    }
+
+    fn open_totp_prompt(&mut self) {
+        if self.totp_prompt.is_none() {
+            self.totp_prompt = Some(TotpPrompt::new(Arc::clone(&self.ctx), false));
+        }
+    }
+
+    fn close_totp_prompt(&mut self) {
+        self.totp_prompt = None;
+        self.ctx.send_action(Action::SwitchToNormalMode);
+    }
+
+    fn retry(&mut self, operation: Operation) {
+        match operation {
+            Operation::Login => self.open_totp_prompt(),
+            Operation::FetchPage(feed, generation) => {
+                let listing = match feed {
+                    Feed::Listing(_) | Feed::Saved => CurrentTab::from_feed(feed)
+                        .and_then(|tab| self.listing_view.listings.get_mut(&tab)),
+                    Feed::Community(_) => self
+                        .community_view
+                        .as_mut()
+                        .and_then(|community_view| community_view.listing.as_mut()),
+                };
+                if let Some(listing) = listing {
+                    if listing.generation == generation {
+                        listing.retry();
+                    }
+                }
+            }
+            Operation::FetchSavedComments(generation) => {
+                if let Some(listing) = self.listing_view.listings.get_mut(&CurrentTab::Saved) {
+                    if listing.generation == generation {
+                        listing.retry_saved_comments();
+                    }
+                }
+            }
+            Operation::FetchCommunity(target) => {
+                if let Some(community_view) = &self.community_view {
+                    if *community_view.target() == target {
+                        community_view.fetch_details();
+                    }
+                }
+            }
+            Operation::FetchCommunities(generation) => {
+                if let Some(community_browser) = &mut self.community_browser {
+                    if community_browser.generation() == generation {
+                        community_browser.retry();
+                    }
+                }
+            }
+            Operation::Search(generation) => {
+                if let Some(search_view) = &mut self.search_view {
+                    if search_view.generation() == generation {
+                        search_view.retry();
+                    }
+                }
+            }
+            Operation::FetchPost(post_id) => {
+                tokio::task::spawn(entry_list::view_post(Arc::clone(&self.ctx), post_id));
+            }
+            Operation::FetchPerson(person_id, saved_only) => {
+                if let Some(profile_view) = &self.profile_view {
+                    if profile_view.person_id() == person_id {
+                        profile_view.fetch(saved_only);
+                    }
+                }
+            }
+            Operation::BlockPerson(person_id, block) => {
+                tokio::task::spawn(profile_view::block_person(
+                    Arc::clone(&self.ctx),
+                    person_id,
+                    block,
+                ));
+            }
+            Operation::SendMessage(recipient_id, content) => {
+                tokio::task::spawn(profile_view::send_message(
+                    Arc::clone(&self.ctx),
+                    recipient_id,
+                    content,
+                ));
+            }
+            Operation::SavePost(post_id, save) => {
+                tokio::task::spawn(types::save_post(Arc::clone(&self.ctx), post_id, save));
+            }
+            Operation::MarkPostRead(post_id) => {
+                tokio::task::spawn(types::mark_post_read(Arc::clone(&self.ctx), post_id));
+            }
+            Operation::SaveComment(comment_id, save) => {
+                tokio::task::spawn(types::save_comment(Arc::clone(&self.ctx), comment_id, save));
+            }
+            Operation::BlockCommunity(community_id, block) => {
+                tokio::task::spawn(community_view::block_community(
+                    Arc::clone(&self.ctx),
+                    community_id,
+                    block,
+                ));
+            }
+            Operation::FollowCommunity(community_id, follow) => {
+                tokio::task::spawn(community_view::follow_community(
+                    Arc::clone(&self.ctx),
+                    community_id,
+                    follow,
+                ));
+            }
+            Operation::FetchComments(post_id) => {
+                if let Some(post_view) = &self.post_view {
+                    if post_view.post.id == post_id {
+                        self.fetch_comments(&post_view.post);
+                    }
+                }
+            }
+            Operation::FetchUnreadCount => {
+                tokio::task::spawn(unreads::fetch_unread_count(Arc::clone(&self.ctx)));
+            }
+            Operation::FetchSite => self.load_site(),
+            Operation::Vote(post_id, score) => {
+                let ctx = Arc::clone(&self.ctx);
+                tokio::task::spawn(async move {
+                    let vote_req = CreatePostLike { post_id, score };
+                    if let Err(e) = ctx.api.like_post(&vote_req).await {
+                        ctx.send_update_action(UpdateAction::Error(
+                            Operation::Vote(post_id, score),
+                            e,
+                        ));
+                    }
+                });
+            }
+            Operation::SwitchProfile(idx) => self.ctx.send_action(Action::SwitchProfile(idx)),
+        }
+        self.ctx.send_action(Action::Render);
+    }
 }
 
 impl Component for MainWindow {
     fn handle_actions(&mut self, action: Action) {
         match action {
+            _ if self.totp_prompt.is_some() => {
+                if let Some(totp_prompt) = &mut self.totp_prompt {
+                    totp_prompt.handle_actions(action);
+                }
+            }
+            _ if self.community_prompt.is_some() => self.handle_community_prompt_action(action),
+            _ if self.message_prompt.is_some() => self.handle_message_prompt_action(action),
+            _ if self.profile_picker.is_some() => self.handle_profile_picker_action(action),
+            _ if self.logout_prompt.is_some() => self.handle_logout_prompt_action(action),
+            _ if self.sort_picker.is_some() => self.handle_sort_picker_action(action),
+            _ if self
+                .search_view
+                .as_ref()
+                .is_some_and(SearchView::is_editing) =>
+            {
+                self.handle_search_input(action)
+            }
+            Action::ShowProfiles => self.open_profile_picker(),
+            Action::Logout => self.open_logout_prompt(),
+            Action::Retry if !self.notifications.is_empty() => {
+                if let Some(operation) = self.notifications.take_latest() {
+                    self.retry(operation);
+                }
+            }
+            Action::Dismiss if !self.notifications.is_empty() => {
+                self.notifications.dismiss();
+                self.ctx.send_action(Action::Render);
+            }
+            Action::OpenCommunity => self.open_focused_community(),
+            Action::GoToCommunity => self.open_community_prompt(),
+            Action::OpenAuthor => self.open_focused_author(),
+            Action::ToggleHideRead if self.post_view.is_none() => {
+                self.ctx.toggle_hide_read();
+                self.reload_listings();
+            }
+            Action::ToggleFiltered if self.post_view.is_none() => {
+                self.ctx.toggle_show_filtered();
+                self.reload_listings();
+            }
+            Action::OpenOwnProfile => {
+                let person_id = self.ctx.site().person_id;
+                if let Some(person_id) = person_id {
+                    self.open_person(person_id);
+                }
+            }
+            Action::BrowseCommunities => {
+                self.post_view = None;
+                self.profile_view = None;
+                self.community_view = None;
+                self.search_view = None;
+                self.community_browser = Some(CommunityBrowser::new(Arc::clone(&self.ctx)));
+                self.ctx.send_action(Action::Render);
+            }
+            Action::Search
+                if self.post_view.is_none()
+                    && self.profile_view.is_none()
+                    && self.community_view.is_none() =>
+            {
+                match &mut self.search_view {
+                    Some(search_view) => search_view.handle_actions(action),
+                    None => self.open_search(),
+                }
+            }
             _ if self.post_view.is_some() => {
                 if let Action::Quit = action {
                     self.post_view = None;
@@ -81,7 +689,66 @@ impl Component for MainWindow {
                     post_view.handle_actions(action);
                 }
             }
-            Action::ChangeSort => self.change_sort(),
+            Action::SendMessage if self.profile_view.is_some() => self.open_message_prompt(),
+            Action::Quit if self.profile_view.is_some() => {
+                self.profile_view = None;
+                self.ctx.send_action(Action::Render);
+            }
+            Action::ChangeTab(_) if self.profile_view.is_some() => {
+                self.profile_view = None;
+                self.community_view = None;
+                self.top_bar.tabs.handle_actions(action);
+                self.ctx.send_action(Action::Render);
+            }
+            _ if self.profile_view.is_some() => {
+                if let Some(profile_view) = &mut self.profile_view {
+                    profile_view.handle_actions(action);
+                }
+            }
+            Action::ChangeSort => self.open_sort_picker(),
+            Action::Quit if self.community_view.is_some() => {
+                self.community_view = None;
+                self.ctx.send_action(Action::Render);
+            }
+            Action::ChangeTab(_) if self.community_view.is_some() => {
+                self.community_view = None;
+                self.top_bar.tabs.handle_actions(action);
+                self.ctx.send_action(Action::Render);
+            }
+            _ if self.community_view.is_some() => {
+                if let Some(community_view) = &mut self.community_view {
+                    community_view.handle_actions(action);
+                }
+            }
+            Action::Quit if self.search_view.is_some() => {
+                self.search_view = None;
+                self.ctx.send_action(Action::Render);
+            }
+            Action::ChangeTab(_) if self.search_view.is_some() => {
+                self.search_view = None;
+                self.top_bar.tabs.handle_actions(action);
+                self.ctx.send_action(Action::Render);
+            }
+            _ if self.search_view.is_some() => {
+                if let Some(search_view) = &mut self.search_view {
+                    search_view.handle_actions(action);
+                }
+            }
+            Action::Quit if self.community_browser.is_some() => {
+                self.community_browser = None;
+                self.ctx.send_action(Action::Render);
+            }
+            Action::ChangeTab(_) if self.community_browser.is_some() => {
+                self.community_browser = None;
+                self.top_bar.tabs.handle_actions(action);
+                self.ctx.send_action(Action::Render);
+            }
+            Action::Confirm if self.community_browser.is_some() => self.open_focused_community(),
+            _ if self.community_browser.is_some() => {
+                if let Some(community_browser) = &mut self.community_browser {
+                    community_browser.handle_actions(action);
+                }
+            }
             Action::Quit => {
                 self.ctx.send_action(Action::ForceQuit);
             }
@@ -93,43 +760,119 @@ impl Component for MainWindow {
     fn handle_update_action(&mut self, action: UpdateAction) {
         match action {
             UpdateAction::UpdateUnreadsCount(unreads_count) => {
-                self.top_bar.unread_counts = unreads_count;
+                self.top_bar.set_unread_count(unreads_count);
                 self.ctx.send_action(Action::Render);
             }
-            UpdateAction::CommentsForCurrentPost(comments) => {
+            UpdateAction::CommentsForPost(post_id, comments) => {
+                // Comments of a post that has since been left are of no use.
                 if let Some(post_view) = &mut self.post_view {
-                    post_view.post.comments = Some(comments.comments.into());
-                    self.ctx.send_action(Action::Render);
+                    if post_view.post.id == post_id {
+                        post_view.set_comments(LemmynatorPostComments::new(
+                            comments,
+                            Arc::clone(&self.ctx),
+                        ));
+                        self.ctx.send_action(Action::Render);
+                    }
                 }
             }
-            UpdateAction::ViewPost(post) => {
-                let params = GetComments {
-                    community_id: Some(post.community_id),
-                    post_id: Some(post.id.clone()),
-                    max_depth: Some(8),
-                    limit: Some(100),
-                    ..Default::default()
-                };
-                self.post_view = Some(PostView::new(post));
+            UpdateAction::ViewPost(mut post) => {
+                post.mark_read();
+                self.fetch_comments(&post);
+                self.post_view = Some(PostView::new(*post));
                 self.ctx.send_action(Action::Render);
-
-                let _ctx = self.ctx.clone();
-                tokio::task::spawn(async move {
-                    let res: GetCommentsResponse = _ctx
-                        .client
-                        .get(format!(
-                            "https://{}/api/v3/comment/list",
-                            CONFIG.connection.instance
-                        ))
-                        .query(&params)
-                        .send()
-                        .await
-                        .unwrap()
-                        .json()
-                        .await
-                        .unwrap();
-                    _ctx.send_update_action(UpdateAction::CommentsForCurrentPost(res));
-                });
+            }
+            UpdateAction::ViewCommunity(target) => self.open_community(target),
+            UpdateAction::ViewPerson(person_id) => self.open_person(person_id),
+            UpdateAction::PersonDetails(_, _, _) => {
+                if let Some(profile_view) = &mut self.profile_view {
+                    profile_view.handle_update_action(action);
+                }
+            }
+            UpdateAction::LoggedIn => {
+                self.close_totp_prompt();
+                tokio::task::spawn(unreads::fetch_unread_count(Arc::clone(&self.ctx)));
+                self.load_site();
+            }
+            UpdateAction::SiteLoaded => self.apply_site_defaults(),
+            UpdateAction::CloseTotpPrompt => self.close_totp_prompt(),
+            UpdateAction::Error(Operation::Login, error) if self.totp_prompt.is_some() => {
+                if let Some(totp_prompt) = &mut self.totp_prompt {
+                    totp_prompt.set_error(&error);
+                }
+                self.ctx.send_action(Action::Render);
+            }
+            UpdateAction::Error(operation, error) => {
+                if error.is_missing_totp() {
+                    self.open_totp_prompt();
+                }
+                match operation {
+                    Operation::FetchPage(Feed::Listing(_) | Feed::Saved, _)
+                    | Operation::FetchSavedComments(_) => {
+                        self.listing_view.handle_update_action(UpdateAction::Error(
+                            operation.clone(),
+                            error.clone(),
+                        ));
+                    }
+                    Operation::FetchPage(Feed::Community(_), _) | Operation::FetchCommunity(_) => {
+                        if let Some(community_view) = &mut self.community_view {
+                            community_view.handle_update_action(UpdateAction::Error(
+                                operation.clone(),
+                                error.clone(),
+                            ));
+                        }
+                    }
+                    Operation::FetchPerson(_, _) => {
+                        if let Some(profile_view) = &mut self.profile_view {
+                            profile_view.handle_update_action(UpdateAction::Error(
+                                operation.clone(),
+                                error.clone(),
+                            ));
+                        }
+                    }
+                    Operation::Search(_) => {
+                        if let Some(search_view) = &mut self.search_view {
+                            search_view.handle_update_action(UpdateAction::Error(
+                                operation.clone(),
+                                error.clone(),
+                            ));
+                        }
+                    }
+                    Operation::FetchCommunities(_) => {
+                        if let Some(community_browser) = &mut self.community_browser {
+                            community_browser.handle_update_action(UpdateAction::Error(
+                                operation.clone(),
+                                error.clone(),
+                            ));
+                        }
+                    }
+                    _ => (),
+                }
+                self.notifications.push(operation, error);
+                self.ctx.send_action(Action::Render);
+            }
+            UpdateAction::NewPage(Feed::Community(_), _, _)
+            | UpdateAction::CommunityDetails(_, _) => {
+                if let Some(community_view) = &mut self.community_view {
+                    community_view.handle_update_action(action);
+                }
+            }
+            UpdateAction::CommunityFollowed(_) | UpdateAction::CommunityBlocked(_) => {
+                if let Some(community_view) = &mut self.community_view {
+                    community_view.handle_update_action(action.clone());
+                }
+                if let Some(community_browser) = &mut self.community_browser {
+                    community_browser.handle_update_action(action);
+                }
+            }
+            UpdateAction::SearchResults(_, _) => {
+                if let Some(search_view) = &mut self.search_view {
+                    search_view.handle_update_action(action);
+                }
+            }
+            UpdateAction::CommunitiesPage(_, _) => {
+                if let Some(community_browser) = &mut self.community_browser {
+                    community_browser.handle_update_action(action);
+                }
             }
             _ => self.listing_view.handle_update_action(action),
         }
@@ -141,8 +884,57 @@ impl Component for MainWindow {
 
         self.top_bar.render(f, top_bar_rect);
 
+        if let Some(totp_prompt) = &mut self.totp_prompt {
+            // Until the first login completes there's nothing we could show.
+            if self.ctx.api.needs_totp() {
+                totp_prompt.render(f, rect);
+                return;
+            }
+        }
+
+        let rect = if self.notifications.is_empty() {
+            rect
+        } else {
+            let [notifications_rect, rect] =
+                Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(rect);
+            self.notifications.render(f, notifications_rect);
+            rect
+        };
+
         if let Some(post_view) = &mut self.post_view {
             post_view.render(f, rect);
+        } else if let Some(profile_view) = &mut self.profile_view {
+            let [_, rect, _] = Layout::horizontal([
+                Constraint::Percentage(5),
+                Constraint::Percentage(90),
+                Constraint::Percentage(5),
+            ])
+            .areas(rect);
+            profile_view.render(f, rect);
+        } else if let Some(community_view) = &mut self.community_view {
+            let [_, rect, _] = Layout::horizontal([
+                Constraint::Percentage(5),
+                Constraint::Percentage(90),
+                Constraint::Percentage(5),
+            ])
+            .areas(rect);
+            community_view.render(f, rect);
+        } else if let Some(search_view) = &mut self.search_view {
+            let [_, rect, _] = Layout::horizontal([
+                Constraint::Percentage(5),
+                Constraint::Percentage(90),
+                Constraint::Percentage(5),
+            ])
+            .areas(rect);
+            search_view.render(f, rect);
+        } else if let Some(community_browser) = &mut self.community_browser {
+            let [_, rect, _] = Layout::horizontal([
+                Constraint::Percentage(5),
+                Constraint::Percentage(90),
+                Constraint::Percentage(5),
+            ])
+            .areas(rect);
+            community_browser.render(f, rect);
         } else {
             let [_, rect, _] = Layout::horizontal([
                 Constraint::Percentage(5),
@@ -156,5 +948,29 @@ impl Component for MainWindow {
                 .expect("Listings already populated")
                 .render(f, rect);
         }
+
+        if let Some(profile_picker) = &mut self.profile_picker {
+            profile_picker.render(f, rect);
+        }
+
+        if let Some(sort_picker) = &mut self.sort_picker {
+            sort_picker.render(f, rect);
+        }
+
+        if let Some(logout_prompt) = &mut self.logout_prompt {
+            logout_prompt.render(f, rect);
+        }
+
+        if let Some(community_prompt) = &mut self.community_prompt {
+            community_prompt.render(f, rect);
+        }
+
+        if let Some((_, message_prompt)) = &mut self.message_prompt {
+            message_prompt.render(f, rect);
+        }
+
+        if let Some(totp_prompt) = &mut self.totp_prompt {
+            totp_prompt.render(f, rect);
+        }
     }
 }
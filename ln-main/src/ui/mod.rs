@@ -1,9 +1,15 @@
+mod community_browser;
+mod community_view;
 pub mod components;
 pub mod listing;
 mod listing_view;
 pub mod main_ui;
+mod notifications;
 mod post_view;
+mod profile_view;
+mod search_view;
 mod top_bar;
+mod totp_prompt;
 
 use ratatui::prelude::*;
 
@@ -0,0 +1,76 @@
+use std::collections::VecDeque;
+
+use ln_config::CONFIG;
+use ratatui::{prelude::*, widgets::Paragraph};
+
+use crate::{action::Operation, client::ClientError};
+
+use super::components::Component;
+
+const MAX_NOTIFICATIONS: usize = 16;
+
+pub struct Notification {
+    pub operation: Operation,
+    pub error: ClientError,
+}
+
+/// Non-blocking list of background failures, shown right under the top bar.
+#[derive(Default)]
+pub struct Notifications {
+    queue: VecDeque<Notification>,
+}
+
+impl Notifications {
+    pub fn push(&mut self, operation: Operation, error: ClientError) {
+        // The same operation failing again replaces the older report.
+        self.queue
+            .retain(|notification| notification.operation != operation);
+        if self.queue.len() == MAX_NOTIFICATIONS {
+            self.queue.pop_front();
+        }
+        self.queue.push_back(Notification { operation, error });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    pub fn dismiss(&mut self) {
+        self.queue.pop_back();
+    }
+
+    /// Removes the most recent notification and hands out its operation, to be retried.
+    pub fn take_latest(&mut self) -> Option<Operation> {
+        self.queue
+            .pop_back()
+            .map(|notification| notification.operation)
+    }
+}
+
+impl Component for Notifications {
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let Some(latest) = self.queue.back() else {
+            return;
+        };
+
+        let key_style = Style::default()
+            .underlined()
+            .fg(CONFIG.general.accent_color);
+
+        let mut spans = vec![
+            Span::styled(" 󰀦 ", Style::new().red()),
+            Span::raw(format!("{} failed: {}. ", latest.operation, latest.error)),
+            Span::raw("Press "),
+            Span::styled("r", key_style),
+            Span::raw(" to retry, "),
+            Span::styled("Esc", key_style),
+            Span::raw(" to dismiss."),
+        ];
+
+        if self.queue.len() > 1 {
+            spans.push(Span::raw(format!(" (+{} more)", self.queue.len() - 1)).dim());
+        }
+
+        f.render_widget(Paragraph::new(Line::from(spans)).centered(), rect);
+    }
+}
@@ -13,24 +13,19 @@ use ratatui_image::Resize;
 
 use crate::{
     action::{Action, UpdateAction},
-    types::{LemmynatorPost, LemmynatorPostCommentsWidget},
+    types::{LemmynatorPost, LemmynatorPostComments, LemmynatorPostCommentsWidget},
 };
 
 use super::components::Component;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 enum CurrentTab {
+    #[default]
     Overview,
     Post,
     Comments,
 }
 
-impl Default for CurrentTab {
-    fn default() -> Self {
-        Self::Overview
-    }
-}
-
 impl Display for CurrentTab {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -45,6 +40,8 @@ pub struct PostView {
     pub post: LemmynatorPost,
     tabs_state: TabsState<CurrentTab>,
     zoom_amount: u16,
+    /// The top level comment in focus on the comments tab.
+    focused_comment: usize,
 }
 
 impl PostView {
@@ -52,6 +49,7 @@ impl PostView {
         Self {
             post,
             zoom_amount: 0,
+            focused_comment: 0,
             tabs_state: TabsState::new(vec![
                 CurrentTab::Overview,
                 CurrentTab::Post,
@@ -59,11 +57,62 @@ impl PostView {
             ]),
         }
     }
+
+    /// Replaces the comments, keeping the one in focus if it's still there.
+    pub fn set_comments(&mut self, comments: LemmynatorPostComments) {
+        let focused_id = self
+            .post
+            .comments
+            .as_ref()
+            .and_then(|old| old.comments.keys().nth(self.focused_comment).copied());
+
+        self.focused_comment = match focused_id {
+            Some(id) => comments
+                .comments
+                .keys()
+                .position(|key| *key == id)
+                .unwrap_or(self.focused_comment),
+            None => self.focused_comment,
+        }
+        .min(comments.comments.len().saturating_sub(1));
+        self.post.comments = Some(comments);
+    }
 }
 
 impl Component for PostView {
     fn handle_actions(&mut self, action: Action) {
+        let on_comments = matches!(self.tabs_state.current(), CurrentTab::Comments);
         match action {
+            Action::Up if on_comments => {
+                self.focused_comment = self.focused_comment.saturating_sub(1);
+                self.post.ctx.send_action(Action::Render);
+            }
+            Action::Down if on_comments => {
+                let count = self
+                    .post
+                    .comments
+                    .as_ref()
+                    .map_or(0, |comments| comments.comments.len());
+                if self.focused_comment + 1 < count {
+                    self.focused_comment += 1;
+                }
+                self.post.ctx.send_action(Action::Render);
+            }
+            Action::ToggleSave if on_comments => {
+                if self.post.ctx.is_anonymous() {
+                    return;
+                }
+                let ctx = Arc::clone(&self.post.ctx);
+                if let Some(comment) =
+                    self.post.comments.as_mut().and_then(|comments| {
+                        comments.comments.values_mut().nth(self.focused_comment)
+                    })
+                {
+                    comment.toggle_save(ctx);
+                    self.post.ctx.send_action(Action::Render);
+                }
+            }
+            Action::ToggleSave | Action::RevealNsfw => self.post.handle_actions(action),
             Action::Up => {
                 self.zoom_amount = self.zoom_amount.saturating_sub(5);
                 self.post.ctx.send_action(Action::Render);
@@ -99,7 +148,7 @@ impl Component for PostView {
             .color(CONFIG.general.accent_color);
         f.render_stateful_widget(tabs, sub_tab, &mut self.tabs_state);
 
-        let spans = vec![
+        let mut spans = vec![
             Span::raw(" << Press "),
             Span::styled(
                 "q",
@@ -121,8 +170,21 @@ impl Component for PostView {
                     .underlined()
                     .fg(CONFIG.general.accent_color),
             ),
-            Span::raw(" to resize the image."),
         ];
+        if self.post.ctx.is_anonymous() {
+            spans.push(Span::raw(" to resize the image or pick a comment."));
+        } else {
+            spans.extend([
+                Span::raw(" to resize the image or pick a comment, "),
+                Span::styled(
+                    "v",
+                    Style::default()
+                        .underlined()
+                        .fg(CONFIG.general.accent_color),
+                ),
+                Span::raw(" to save."),
+            ]);
+        }
 
         let how_to_quit = Paragraph::new(Line::from(spans));
 
@@ -261,6 +323,7 @@ impl Component for PostView {
                             &mut comments.comments,
                         )
                         .left_sife_width(left_side_rect.width)
+                        .focused(Some(self.focused_comment))
                         .render(f, comments_rect);
                     }
                 }
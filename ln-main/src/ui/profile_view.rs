@@ -0,0 +1,526 @@
+use std::{
+    fmt::Display,
+    sync::{Arc, Mutex},
+};
+
+use intui_tabs::{Tabs, TabsState};
+use lemmy_api_common::{
+    lemmy_db_schema::{
+        newtypes::{CommunityId, PersonId},
+        SortType,
+    },
+    person::{BlockPerson, GetPersonDetails},
+    private_message::CreatePrivateMessage,
+};
+use ln_config::CONFIG;
+use ratatui::{
+    prelude::*,
+    widgets::{Paragraph, Wrap},
+};
+use ratskin::RatSkin;
+
+use crate::{
+    action::{Action, Operation, UpdateAction},
+    app::Ctx,
+    client::ClientError,
+    types::{LemmynatorPost, PersonDetails, ThreadImage},
+};
+
+use super::{
+    centered_rect,
+    components::{
+        entry_list::{Entry, EntryList},
+        Component,
+    },
+    listing::page::Page,
+};
+
+/// How many posts and comments are shown, there's no paging through them.
+const CONTENT_LIMIT: i64 = 50;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum ProfileTab {
+    #[default]
+    Overview,
+    Posts,
+    Comments,
+    Saved,
+}
+
+impl Display for ProfileTab {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProfileTab::Overview => write!(f, "Overview"),
+            ProfileTab::Posts => write!(f, "Posts"),
+            ProfileTab::Comments => write!(f, "Comments"),
+            ProfileTab::Saved => write!(f, "Saved"),
+        }
+    }
+}
+
+/// Someone's profile: who they are, and what they posted and commented.
+pub struct ProfileView {
+    person_id: PersonId,
+    tabs_state: TabsState<ProfileTab>,
+    tabs_count: usize,
+    details: Option<PersonDetails>,
+    avatar: Arc<Mutex<Option<ThreadImage>>>,
+    banner: Arc<Mutex<Option<ThreadImage>>>,
+    posts: Page,
+    comments: EntryList,
+    /// Only there for your own profile, and loaded once it's looked at.
+    saved: Option<EntryList>,
+    error: Option<ClientError>,
+    ctx: Arc<Ctx>,
+}
+
+impl ProfileView {
+    pub fn new(person_id: PersonId, ctx: Arc<Ctx>) -> Self {
+        let mut tabs = vec![
+            ProfileTab::Overview,
+            ProfileTab::Posts,
+            ProfileTab::Comments,
+        ];
+        // Nobody gets to see what others saved.
+        if ctx.site().person_id == Some(person_id) {
+            tabs.push(ProfileTab::Saved);
+        }
+
+        let profile_view = Self {
+            person_id,
+            tabs_count: tabs.len(),
+            tabs_state: TabsState::new(tabs),
+            details: None,
+            avatar: Arc::new(Mutex::new(None)),
+            banner: Arc::new(Mutex::new(None)),
+            posts: Page::new(Arc::clone(&ctx)),
+            comments: EntryList::default(),
+            saved: None,
+            error: None,
+            ctx,
+        };
+        profile_view.fetch(false);
+        profile_view
+    }
+
+    pub fn person_id(&self) -> PersonId {
+        self.person_id
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.details
+            .as_ref()
+            .map(|details| details.info.person.name.as_str())
+    }
+
+    fn is_own(&self) -> bool {
+        self.ctx.site().person_id == Some(self.person_id)
+    }
+
+    fn is_blocked(&self) -> bool {
+        self.ctx.site().blocked_people.contains(&self.person_id)
+    }
+
+    /// The community the post or comment in focus comes from.
+    pub fn focused_community_id(&self) -> Option<CommunityId> {
+        match self.tabs_state.current() {
+            ProfileTab::Overview => None,
+            ProfileTab::Posts => self.posts.focused_post().map(|post| post.community_id),
+            ProfileTab::Comments => self.comments.focused().and_then(Entry::community_id),
+            ProfileTab::Saved => self
+                .saved
+                .as_ref()
+                .and_then(EntryList::focused)
+                .and_then(Entry::community_id),
+        }
+    }
+
+    pub fn fetch(&self, saved_only: bool) {
+        let req = GetPersonDetails {
+            person_id: Some(self.person_id),
+            sort: Some(SortType::New),
+            limit: Some(CONTENT_LIMIT),
+            saved_only: saved_only.then_some(true),
+            ..Default::default()
+        };
+
+        let person_id = self.person_id;
+        let ctx = Arc::clone(&self.ctx);
+        tokio::task::spawn(async move {
+            match ctx.api.get_person_details(&req).await {
+                Ok(res) => ctx.send_update_action(UpdateAction::PersonDetails(
+                    person_id,
+                    saved_only,
+                    Box::new(res),
+                )),
+                Err(e) => ctx.send_update_action(UpdateAction::Error(
+                    Operation::FetchPerson(person_id, saved_only),
+                    e,
+                )),
+            }
+            ctx.send_action(Action::Render);
+        });
+    }
+
+    fn show_details(&mut self, details: PersonDetails) {
+        // Refreshing the details doesn't bring new images.
+        if self.details.is_none() {
+            for (url, slot) in [
+                (&details.info.person.avatar, &self.avatar),
+                (&details.info.banner, &self.banner),
+            ] {
+                if let Some(url) = url {
+                    tokio::task::spawn(ThreadImage::fetch(
+                        url.to_string(),
+                        Arc::clone(slot),
+                        Arc::clone(&self.ctx),
+                    ));
+                }
+            }
+        }
+
+        self.posts = Page::new(Arc::clone(&self.ctx));
+        self.posts.posts = details
+            .posts
+            .iter()
+            .map(|post| LemmynatorPost::from_lemmy_post(post.clone(), Arc::clone(&self.ctx)))
+            .collect();
+        self.posts.all_posts_count = self.posts.posts.len();
+        self.comments = EntryList::new(
+            details
+                .comments
+                .iter()
+                .map(|comment| Entry::Comment(Box::new(comment.clone())))
+                .collect(),
+            false,
+        );
+        self.details = Some(details);
+    }
+
+    fn show_saved(&mut self, saved: PersonDetails) {
+        let entries = saved
+            .posts
+            .into_iter()
+            .map(|post| Entry::Post(Box::new(post)))
+            .chain(
+                saved
+                    .comments
+                    .into_iter()
+                    .map(|comment| Entry::Comment(Box::new(comment))),
+            )
+            .collect();
+        self.saved = Some(EntryList::new(entries, true));
+    }
+
+    fn toggle_block(&self) {
+        if self.ctx.is_anonymous() || self.is_own() {
+            return;
+        }
+
+        tokio::task::spawn(block_person(
+            Arc::clone(&self.ctx),
+            self.person_id,
+            !self.is_blocked(),
+        ));
+    }
+
+    /// Moves through, or opens, what the current tab lists.
+    fn handle_content_action(&mut self, action: Action) {
+        let list = match self.tabs_state.current() {
+            ProfileTab::Overview => return,
+            ProfileTab::Posts => {
+                // Unlike a listing, there's no next page to scroll into.
+                let at_end = action == Action::Down && self.posts.is_last_focused();
+                if !self.posts.posts.is_empty() && !at_end {
+                    self.posts.handle_actions(action);
+                }
+                return;
+            }
+            ProfileTab::Comments => &mut self.comments,
+            ProfileTab::Saved => match &mut self.saved {
+                Some(saved) => saved,
+                None => return,
+            },
+        };
+
+        match action {
+            Action::Confirm => {
+                if let Some(entry) = list.focused() {
+                    entry.open(&self.ctx);
+                }
+            }
+            action => list.handle_actions(action),
+        }
+    }
+
+    fn overview_lines(&self, details: &PersonDetails, width: u16) -> Vec<Line<'static>> {
+        let info = &details.info;
+        let person = &info.person;
+        let accent = Style::default().fg(CONFIG.general.accent_color);
+        let key_style = Style::default()
+            .underlined()
+            .fg(CONFIG.general.accent_color);
+
+        let mut title = vec![Span::styled(
+            person
+                .display_name
+                .clone()
+                .unwrap_or_else(|| person.name.clone()),
+            accent.bold(),
+        )];
+        if info.is_admin {
+            title.push(Span::styled(" admin", accent));
+        }
+        if info.bot_account {
+            title.push(Span::raw(" bot").dim());
+        }
+        if info.banned {
+            title.push(Span::styled(" banned", Style::new().red()));
+        }
+
+        let mut lines = vec![
+            Line::from(title),
+            Line::raw(format!("u/{}@{}", person.name, person.host)).dim(),
+            Line::default(),
+            Line::raw(format!(" Joined {}", info.published.date_naive())),
+            Line::raw(format!(
+                " {} posts, {} comments",
+                info.post_count, info.comment_count
+            )),
+            Line::default(),
+        ];
+
+        if !self.ctx.is_anonymous() && !self.is_own() {
+            let (status, hint) = match self.is_blocked() {
+                true => ("Blocked", " to unblock"),
+                false => ("Not blocked", " to block"),
+            };
+            lines.push(Line::from(vec![
+                Span::raw(format!("{status}, press ")),
+                Span::styled("B", key_style),
+                Span::raw(hint),
+            ]));
+            lines.push(Line::from(vec![
+                Span::raw("Press "),
+                Span::styled("p", key_style),
+                Span::raw(" to send a private message"),
+            ]));
+            lines.push(Line::default());
+        }
+
+        if let Some(bio) = &info.bio {
+            let rat_skin = RatSkin::default();
+            let text = RatSkin::parse_text(bio);
+            lines.extend(rat_skin.parse(text, width));
+            lines.push(Line::default());
+        }
+
+        if !details.moderates.is_empty() {
+            lines.push(Line::styled("Moderates", accent.bold()));
+            lines.extend(
+                details
+                    .moderates
+                    .iter()
+                    .map(|community| Line::raw(format!("c/{}", community.name))),
+            );
+        }
+
+        lines
+    }
+
+    fn render_overview(&mut self, f: &mut Frame, rect: Rect) {
+        let Some(details) = &self.details else {
+            return;
+        };
+
+        let rect = if let Some(banner) = &mut *self.banner.lock().unwrap() {
+            let [banner_rect, _, rect] = Layout::vertical([
+                Constraint::Length(8),
+                Constraint::Length(1),
+                Constraint::Fill(1),
+            ])
+            .areas(rect);
+            banner.render(f, banner_rect, Arc::clone(&self.ctx));
+            rect
+        } else {
+            rect
+        };
+
+        let [avatar_rect, _, text_rect] = Layout::horizontal([
+            Constraint::Length(12),
+            Constraint::Length(2),
+            Constraint::Fill(1),
+        ])
+        .areas(rect);
+
+        if let Some(avatar) = &mut *self.avatar.lock().unwrap() {
+            let avatar_rect = Rect {
+                height: avatar_rect.height.min(6),
+                ..avatar_rect
+            };
+            avatar.render(f, avatar_rect, Arc::clone(&self.ctx));
+        }
+
+        let overview = Paragraph::new(self.overview_lines(details, text_rect.width))
+            .wrap(Wrap { trim: false });
+        f.render_widget(overview, text_rect);
+    }
+
+    fn render_message(&self, f: &mut Frame, rect: Rect, message: Line<'static>) {
+        let message_rect = centered_rect(rect, 80, 10);
+        f.render_widget(
+            Paragraph::new(message).alignment(Alignment::Center),
+            message_rect,
+        );
+    }
+
+    fn render_bottom_bar(&self, f: &mut Frame, rect: Rect) {
+        let key_style = Style::default()
+            .underlined()
+            .fg(CONFIG.general.accent_color);
+
+        let mut spans = vec![Span::raw(" 󰋗 Press ")];
+        if !self.ctx.is_anonymous() && !self.is_own() {
+            spans.extend([
+                Span::styled("B", key_style),
+                Span::raw(" to block, "),
+                Span::styled("p", key_style),
+                Span::raw(" to send a message, "),
+            ]);
+        }
+        spans.extend([Span::styled("q", key_style), Span::raw(" to go back.")]);
+
+        f.render_widget(Paragraph::new(Line::from(spans)), rect);
+    }
+}
+
+/// Blocks or unblocks someone, keeping the known blocks in sync.
+pub async fn block_person(ctx: Arc<Ctx>, person_id: PersonId, block: bool) {
+    let req = BlockPerson { person_id, block };
+
+    match ctx.api.block_person(&req).await {
+        Ok(blocked) => {
+            let mut site = ctx.site_mut();
+            if blocked {
+                site.blocked_people.insert(person_id);
+            } else {
+                site.blocked_people.remove(&person_id);
+            }
+        }
+        Err(e) => ctx.send_update_action(UpdateAction::Error(
+            Operation::BlockPerson(person_id, block),
+            e,
+        )),
+    }
+    ctx.send_action(Action::Render);
+}
+
+pub async fn send_message(ctx: Arc<Ctx>, recipient_id: PersonId, content: String) {
+    let req = CreatePrivateMessage {
+        content: content.clone(),
+        recipient_id,
+    };
+
+    if let Err(e) = ctx.api.create_private_message(&req).await {
+        ctx.send_update_action(UpdateAction::Error(
+            Operation::SendMessage(recipient_id, content),
+            e,
+        ));
+    }
+    ctx.send_action(Action::Render);
+}
+
+impl Component for ProfileView {
+    fn handle_actions(&mut self, action: Action) {
+        match action {
+            Action::ChangeSubTab(n) if usize::from(n) <= self.tabs_count => {
+                self.tabs_state.set(n.into());
+                if self.tabs_state.current() == ProfileTab::Saved && self.saved.is_none() {
+                    self.fetch(true);
+                }
+                self.ctx.send_action(Action::Render);
+            }
+            Action::ToggleBlock => self.toggle_block(),
+            Action::Retry if self.error.is_some() => {
+                self.error = None;
+                self.fetch(false);
+            }
+            _ => {
+                self.handle_content_action(action);
+                self.ctx.send_action(Action::Render);
+            }
+        }
+    }
+
+    fn handle_update_action(&mut self, action: UpdateAction) {
+        match action {
+            UpdateAction::PersonDetails(person_id, false, details)
+                if person_id == self.person_id =>
+            {
+                self.error = None;
+                self.show_details(*details);
+            }
+            UpdateAction::PersonDetails(person_id, true, saved) if person_id == self.person_id => {
+                self.show_saved(*saved);
+            }
+            UpdateAction::Error(Operation::FetchPerson(person_id, false), error)
+                if person_id == self.person_id =>
+            {
+                self.error = Some(error);
+            }
+            _ => (),
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let [sub_tab_rect, _, main_rect, bottom_bar_rect] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(rect);
+
+        let tabs = Tabs::new()
+            .center(true)
+            .beginner_mode(true)
+            .sub_tab(true)
+            .color(CONFIG.general.accent_color);
+        f.render_stateful_widget(tabs, sub_tab_rect, &mut self.tabs_state);
+
+        if self.details.is_none() {
+            let message = match &self.error {
+                Some(error) => Line::styled(
+                    format!("Could not load the profile: {error}"),
+                    Style::new().red(),
+                ),
+                None => Line::raw("Loading the profile...").dim(),
+            };
+            self.render_message(f, main_rect, message);
+            self.render_bottom_bar(f, bottom_bar_rect);
+            return;
+        }
+
+        match self.tabs_state.current() {
+            ProfileTab::Overview => self.render_overview(f, main_rect),
+            ProfileTab::Posts if self.posts.posts.is_empty() => {
+                self.render_message(f, main_rect, Line::raw("No posts yet.").dim())
+            }
+            ProfileTab::Posts => self.posts.render(f, main_rect),
+            ProfileTab::Comments if self.comments.is_empty() => {
+                self.render_message(f, main_rect, Line::raw("No comments yet.").dim())
+            }
+            ProfileTab::Comments => self.comments.render(f, main_rect),
+            ProfileTab::Saved => match &mut self.saved {
+                Some(saved) if saved.is_empty() => {
+                    let message = Line::raw("Nothing saved yet.").dim();
+                    self.render_message(f, main_rect, message);
+                }
+                Some(saved) => saved.render(f, main_rect),
+                None => self.render_message(f, main_rect, Line::raw("Loading...").dim()),
+            },
+        }
+
+        self.render_bottom_bar(f, bottom_bar_rect);
+    }
+}
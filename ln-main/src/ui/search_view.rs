@@ -0,0 +1,475 @@
+use std::{fmt::Display, sync::Arc};
+
+use crossterm::event::{Event, KeyCode};
+use intui_tabs::{Tabs, TabsState};
+use lemmy_api_common::{
+    lemmy_db_schema::{
+        newtypes::{CommunityId, PersonId},
+        ListingType, SearchType, SortType,
+    },
+    site::Search,
+};
+use ln_config::CONFIG;
+use ratatui::{
+    prelude::*,
+    widgets::{Block, BorderType, Paragraph},
+};
+use tokio_util::sync::CancellationToken;
+use tui_input::{backend::crossterm::EventHandler, Input};
+
+use crate::{
+    action::{Action, Operation, UpdateAction},
+    app::Ctx,
+    client::ClientError,
+    types::{LemmynatorPost, SearchResults},
+};
+
+use super::{
+    centered_rect,
+    components::{
+        entry_list::{Entry, EntryList},
+        tabs::sort_name,
+        Component,
+    },
+    listing::{next_generation, page::Page},
+};
+
+/// How many results a search brings, there's no paging through them.
+const RESULTS_LIMIT: i64 = 50;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum SearchTab {
+    #[default]
+    All,
+    Posts,
+    Comments,
+    Communities,
+    Users,
+    Url,
+}
+
+impl SearchTab {
+    const ALL: [SearchTab; 6] = [
+        SearchTab::All,
+        SearchTab::Posts,
+        SearchTab::Comments,
+        SearchTab::Communities,
+        SearchTab::Users,
+        SearchTab::Url,
+    ];
+
+    fn search_type(&self) -> SearchType {
+        match self {
+            SearchTab::All => SearchType::All,
+            SearchTab::Posts => SearchType::Posts,
+            SearchTab::Comments => SearchType::Comments,
+            SearchTab::Communities => SearchType::Communities,
+            SearchTab::Users => SearchType::Users,
+            SearchTab::Url => SearchType::Url,
+        }
+    }
+
+    /// Posts are shown the way listings show them, anything else as a list.
+    fn shows_posts(&self) -> bool {
+        matches!(self, SearchTab::Posts | SearchTab::Url)
+    }
+}
+
+impl Display for SearchTab {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SearchTab::All => write!(f, "All"),
+            SearchTab::Posts => write!(f, "Posts"),
+            SearchTab::Comments => write!(f, "Comments"),
+            SearchTab::Communities => write!(f, "Communities"),
+            SearchTab::Users => write!(f, "Users"),
+            SearchTab::Url => write!(f, "URL"),
+        }
+    }
+}
+
+/// A search bar with the results below it, split by what they are.
+pub struct SearchView {
+    generation: u64,
+    cancel: CancellationToken,
+    input: Input,
+    /// Keys go to the search bar rather than the results.
+    editing: bool,
+    query: Option<String>,
+    tabs_state: TabsState<SearchTab>,
+    listing_type: ListingType,
+    sort_type: SortType,
+    searching: bool,
+    posts: Page,
+    results: EntryList,
+    error: Option<ClientError>,
+    ctx: Arc<Ctx>,
+}
+
+impl SearchView {
+    pub fn new(listing_type: ListingType, ctx: Arc<Ctx>) -> Self {
+        ctx.send_action(Action::SwitchToInputMode);
+
+        Self {
+            generation: next_generation(),
+            cancel: CancellationToken::new(),
+            input: Input::default(),
+            editing: true,
+            query: None,
+            tabs_state: TabsState::new(SearchTab::ALL.to_vec()),
+            listing_type,
+            sort_type: SortType::TopAll,
+            searching: false,
+            posts: Page::new(Arc::clone(&ctx)),
+            results: EntryList::default(),
+            error: None,
+            ctx,
+        }
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editing
+    }
+
+    /// The search bar was left without ever searching for anything.
+    pub fn is_abandoned(&self) -> bool {
+        !self.editing && self.query.is_none()
+    }
+
+    pub fn sort_type(&self) -> SortType {
+        self.sort_type
+    }
+
+    pub fn set_sort(&mut self, sort_type: SortType) {
+        self.sort_type = sort_type;
+        self.search();
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The community the result in focus comes from or is.
+    pub fn focused_community_id(&self) -> Option<CommunityId> {
+        match self.tabs_state.current().shows_posts() {
+            true => self.posts.focused_post().map(|post| post.community_id),
+            false => self.results.focused().and_then(Entry::community_id),
+        }
+    }
+
+    /// Who wrote the result in focus, or who it is.
+    pub fn focused_person_id(&self) -> Option<PersonId> {
+        match self.tabs_state.current().shows_posts() {
+            true => self.posts.focused_post().map(|post| post.creator_id),
+            false => self.results.focused().and_then(Entry::person_id),
+        }
+    }
+
+    fn start_editing(&mut self) {
+        self.editing = true;
+        self.ctx.send_action(Action::SwitchToInputMode);
+        self.ctx.send_action(Action::Render);
+    }
+
+    fn stop_editing(&mut self) {
+        self.editing = false;
+        self.ctx.send_action(Action::SwitchToNormalMode);
+        self.ctx.send_action(Action::Render);
+    }
+
+    fn handle_input(&mut self, action: Action) {
+        let Action::Input(key) = action else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Enter if !self.input.value().trim().is_empty() => {
+                self.query = Some(self.input.value().trim().to_string());
+                self.stop_editing();
+                self.search();
+            }
+            KeyCode::Esc => {
+                // Going back to the results leaves the bar as it was searched.
+                self.input = Input::new(self.query.clone().unwrap_or_default());
+                self.stop_editing();
+            }
+            _ => {
+                self.input.handle_event(&Event::Key(key));
+                self.ctx.send_action(Action::Render);
+            }
+        }
+    }
+
+    fn next_listing_type(&mut self) {
+        self.listing_type = match self.listing_type {
+            ListingType::All => ListingType::Local,
+            ListingType::Local if !self.ctx.is_anonymous() => ListingType::Subscribed,
+            _ => ListingType::All,
+        };
+        self.search();
+    }
+
+    /// Searches for the query anew, with the current filters.
+    fn search(&mut self) {
+        self.cancel.cancel();
+        self.cancel = CancellationToken::new();
+        self.generation = next_generation();
+        self.posts = Page::new(Arc::clone(&self.ctx));
+        self.results = EntryList::default();
+        self.error = None;
+        self.ctx.send_action(Action::Render);
+
+        self.searching = self.query.is_some();
+        let Some(query) = self.query.clone() else {
+            return;
+        };
+
+        let req = Search {
+            q: query,
+            type_: Some(self.tabs_state.current().search_type()),
+            sort: Some(self.sort_type),
+            listing_type: Some(self.listing_type),
+            limit: Some(RESULTS_LIMIT),
+            ..Default::default()
+        };
+        let generation = self.generation;
+        let cancel = self.cancel.clone();
+        let ctx = Arc::clone(&self.ctx);
+
+        tokio::task::spawn(async move {
+            let res = tokio::select! {
+                _ = cancel.cancelled() => return,
+                res = ctx.api.search(&req) => res,
+            };
+
+            match res {
+                Ok(res) => {
+                    ctx.send_update_action(UpdateAction::SearchResults(generation, Box::new(res)))
+                }
+                Err(e) => {
+                    ctx.send_update_action(UpdateAction::Error(Operation::Search(generation), e))
+                }
+            }
+            ctx.send_action(Action::Render);
+        });
+    }
+
+    pub fn retry(&mut self) {
+        self.search();
+    }
+
+    fn show_results(&mut self, res: SearchResults) {
+        if self.tabs_state.current().shows_posts() {
+            self.posts.posts = res
+                .posts
+                .into_iter()
+                .map(|post| LemmynatorPost::from_lemmy_post(post, Arc::clone(&self.ctx)))
+                .collect();
+            self.posts.all_posts_count = self.posts.posts.len();
+            return;
+        }
+
+        // The few communities and users matching are easily lost below the posts.
+        let entries = res
+            .communities
+            .into_iter()
+            .map(|info| Entry::Community(Box::new(info)))
+            .chain(
+                res.users
+                    .into_iter()
+                    .map(|info| Entry::User(Box::new(info))),
+            )
+            .chain(
+                res.posts
+                    .into_iter()
+                    .map(|post| Entry::Post(Box::new(post))),
+            )
+            .chain(
+                res.comments
+                    .into_iter()
+                    .map(|comment| Entry::Comment(Box::new(comment))),
+            )
+            .collect();
+        let show_kinds = self.tabs_state.current() == SearchTab::All;
+        self.results = EntryList::new(entries, show_kinds);
+    }
+
+    fn handle_posts_action(&mut self, action: Action) {
+        if self.posts.posts.is_empty() {
+            return;
+        }
+
+        // Unlike a listing, there's no next page to scroll into.
+        if action == Action::Down && self.posts.is_last_focused() {
+            return;
+        }
+        self.posts.handle_actions(action);
+    }
+
+    fn render_search_bar(&self, f: &mut Frame, rect: Rect) {
+        let border_style = if self.editing {
+            Style::default().fg(CONFIG.general.accent_color)
+        } else {
+            Style::new().dim()
+        };
+        let block = Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(border_style)
+            .title(" Search ");
+        let inner_rect = block.inner(rect);
+        f.render_widget(block, rect);
+
+        let filters = Line::raw(format!(
+            "{} · {} ",
+            self.listing_type,
+            sort_name(self.sort_type)
+        ))
+        .dim()
+        .right_aligned();
+        f.render_widget(filters, inner_rect);
+
+        let scroll = self.input.visual_scroll(inner_rect.width as usize);
+        f.render_widget(
+            Paragraph::new(self.input.value()).scroll((0, scroll as u16)),
+            inner_rect,
+        );
+
+        if self.editing {
+            f.set_cursor_position((
+                inner_rect.x + (self.input.visual_cursor().saturating_sub(scroll)) as u16,
+                inner_rect.y,
+            ));
+        }
+    }
+
+    fn render_message(&self, f: &mut Frame, rect: Rect, message: Line<'static>) {
+        let message_rect = centered_rect(rect, 80, 10);
+        f.render_widget(
+            Paragraph::new(message).alignment(Alignment::Center),
+            message_rect,
+        );
+    }
+
+    fn render_bottom_bar(&self, f: &mut Frame, rect: Rect) {
+        let key_style = Style::default()
+            .underlined()
+            .fg(CONFIG.general.accent_color);
+
+        let spans = if self.editing {
+            vec![
+                Span::raw(" 󰋗 Press "),
+                Span::styled("Enter", key_style),
+                Span::raw(" to search, "),
+                Span::styled("Esc", key_style),
+                Span::raw(" to stop typing."),
+            ]
+        } else {
+            vec![
+                Span::raw(" 󰋗 Press "),
+                Span::styled("/", key_style),
+                Span::raw(" to search again, "),
+                Span::styled("l", key_style),
+                Span::raw(" to change where, "),
+                Span::styled("4", key_style),
+                Span::raw(" to sort, "),
+                Span::styled("q", key_style),
+                Span::raw(" to go back."),
+            ]
+        };
+
+        f.render_widget(Paragraph::new(Line::from(spans)), rect);
+    }
+}
+
+impl Drop for SearchView {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+impl Component for SearchView {
+    fn handle_actions(&mut self, action: Action) {
+        if self.editing {
+            self.handle_input(action);
+            return;
+        }
+
+        match action {
+            Action::Search => self.start_editing(),
+            Action::ChangeListingType => self.next_listing_type(),
+            Action::ChangeSubTab(n) if usize::from(n) <= SearchTab::ALL.len() => {
+                let previous = self.tabs_state.current();
+                self.tabs_state.set(n.into());
+                if self.tabs_state.current() != previous {
+                    self.search();
+                }
+            }
+            Action::Retry if self.error.is_some() => self.retry(),
+            _ if self.tabs_state.current().shows_posts() => self.handle_posts_action(action),
+            Action::Confirm => {
+                if let Some(entry) = self.results.focused() {
+                    entry.open(&self.ctx);
+                }
+            }
+            _ => {
+                self.results.handle_actions(action);
+                self.ctx.send_action(Action::Render);
+            }
+        }
+    }
+
+    fn handle_update_action(&mut self, action: UpdateAction) {
+        match action {
+            UpdateAction::SearchResults(generation, res) if generation == self.generation => {
+                self.searching = false;
+                self.show_results(*res);
+            }
+            UpdateAction::Error(Operation::Search(generation), error)
+                if generation == self.generation =>
+            {
+                self.searching = false;
+                self.error = Some(error);
+            }
+            _ => (),
+        }
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        let [search_bar_rect, sub_tab_rect, _, results_rect, bottom_bar_rect] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Fill(1),
+            Constraint::Length(1),
+        ])
+        .areas(rect);
+
+        self.render_search_bar(f, search_bar_rect);
+
+        let tabs = Tabs::new()
+            .center(true)
+            .beginner_mode(true)
+            .sub_tab(true)
+            .color(CONFIG.general.accent_color);
+        f.render_stateful_widget(tabs, sub_tab_rect, &mut self.tabs_state);
+
+        let shows_posts = self.tabs_state.current().shows_posts();
+        if shows_posts && !self.posts.posts.is_empty() {
+            self.posts.render(f, results_rect);
+        } else if !shows_posts && !self.results.is_empty() {
+            self.results.render(f, results_rect);
+        } else if let Some(error) = &self.error {
+            let message = Line::styled(format!("Could not search: {error}"), Style::new().red());
+            self.render_message(f, results_rect, message);
+        } else if self.searching {
+            self.render_message(f, results_rect, Line::raw("Searching...").dim());
+        } else if self.query.is_some() {
+            self.render_message(f, results_rect, Line::raw("Nothing found.").dim());
+        } else {
+            let message = Line::raw("Type what you're looking for and press Enter.").dim();
+            self.render_message(f, results_rect, message);
+        }
+
+        self.render_bottom_bar(f, bottom_bar_rect);
+    }
+}
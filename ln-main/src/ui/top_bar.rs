@@ -1,53 +1,134 @@
-use std::sync::Arc;
+use std::{
+    io::{stdout, Write},
+    sync::{Arc, Mutex},
+};
 
-use lemmy_api_common::person::GetUnreadCountResponse;
+use crossterm::{execute, terminal::SetTitle};
 use ln_config::CONFIG;
 use ratatui::{
     layout::Rect,
-    style::Style,
+    style::{Style, Stylize},
     text::{Line, Span},
     widgets::Paragraph,
     Frame,
 };
 
-use crate::app::Ctx;
+use crate::{app::Ctx, types::ThreadImage};
 
 use super::components::{tabs::TabComponent, Component};
 
 pub struct TopBar {
     pub tabs: TabComponent,
-    pub unread_counts: GetUnreadCountResponse,
+    /// Unread replies, mentions and private messages.
+    pub unread_count: i64,
+    /// Whether the count was fetched at least once, so unreads from before
+    /// the start aren't announced as new ones.
+    unread_count_known: bool,
+    icon: Arc<Mutex<Option<ThreadImage>>>,
+    ctx: Arc<Ctx>,
 }
 
 impl TopBar {
-    pub async fn new(ctx: Arc<Ctx>, unread_counts: GetUnreadCountResponse) -> Self {
-        Self {
+    pub async fn new(ctx: Arc<Ctx>, unread_count: i64) -> Self {
+        let top_bar = Self {
             tabs: TabComponent::new(Arc::clone(&ctx)),
-            unread_counts,
+            unread_count,
+            unread_count_known: false,
+            icon: Arc::new(Mutex::new(None)),
+            ctx,
+        };
+        top_bar.load_icon();
+        top_bar
+    }
+
+    /// Fetches the instance's icon, unless there's none or it's already there.
+    pub fn load_icon(&self) {
+        if self.icon.lock().unwrap().is_some() {
+            return;
+        }
+        if let Some(url) = self.ctx.site().icon.clone() {
+            tokio::task::spawn(ThreadImage::fetch(
+                url,
+                Arc::clone(&self.icon),
+                Arc::clone(&self.ctx),
+            ));
+        }
+    }
+
+    pub fn set_unread_count(&mut self, unread_count: i64) {
+        let previous = self.unread_count;
+        self.unread_count = unread_count;
+        let total = unread_count;
+
+        let notify = CONFIG.unreads.notify;
+        if notify.title() && (total != previous || !self.unread_count_known) {
+            let title = match total {
+                0 => "Lemmynator".to_string(),
+                total => format!("({total}) Lemmynator"),
+            };
+            let _ = execute!(stdout(), SetTitle(title));
+        }
+        if notify.bell() && self.unread_count_known && total > previous {
+            let mut stdout = stdout();
+            let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
         }
+
+        self.unread_count_known = true;
     }
 
-    fn total_unreads(&self) -> i64 {
-        let unread_counts = &self.unread_counts;
-        unread_counts.replies + unread_counts.mentions + unread_counts.private_messages
+    fn username(&self) -> &str {
+        self.ctx.profile.username.as_deref().unwrap_or("guest")
+    }
+
+    fn unreads_span(&self) -> Span<'_> {
+        let total_unreads = self.unread_count;
+        if total_unreads == 0 {
+            Span::raw(" 󰂚 ")
+        } else {
+            Span::styled(
+                format!(" 󱅫 {total_unreads}"),
+                Style::new().fg(CONFIG.general.accent_color),
+            )
+        }
+    }
+
+    /// The instance's icon when it has one, a generic one otherwise, followed by its name.
+    fn render_instance(&mut self, f: &mut Frame, rect: Rect) {
+        let name = self.ctx.site().name.clone();
+
+        if let Some(icon) = &mut *self.icon.lock().unwrap() {
+            let icon_rect = Rect {
+                x: rect.x + 1,
+                width: 2,
+                height: 1,
+                ..rect
+            };
+            icon.render(f, icon_rect, Arc::clone(&self.ctx));
+
+            let name_rect = Rect {
+                x: rect.x + 4,
+                width: rect.width.saturating_sub(4),
+                ..rect
+            };
+            f.render_widget(Paragraph::new(name).left_aligned(), name_rect);
+        } else {
+            let paragraph = Paragraph::new(format!(" {name}")).left_aligned();
+            f.render_widget(paragraph, rect);
+        }
     }
 
     fn menu_text(&self) -> Line<'_> {
-        let total_unreads = self.total_unreads();
         let mut spans = vec![];
 
-        spans.push({
-            if total_unreads == 0 {
-                Span::raw(" 󰂚 ")
-            } else {
-                Span::styled(
-                    format!(" 󱅫 {total_unreads}"),
-                    Style::new().fg(CONFIG.general.accent_color),
-                )
-            }
-        });
-
-        spans.push(Span::raw(format!("   {}  ", &CONFIG.connection.username)));
+        if self.ctx.api.is_offline() {
+            spans.push(Span::styled(" offline ", Style::new().red().bold()));
+        }
+
+        if !self.ctx.is_anonymous() {
+            spans.push(self.unreads_span());
+        }
+
+        spans.push(Span::raw(format!("   {}  ", self.username())));
         Line::from(spans)
     }
 }
@@ -57,8 +138,7 @@ impl Component for TopBar {
         let paragraph = Paragraph::new(self.menu_text()).right_aligned();
         f.render_widget(paragraph, rect);
 
-        let paragraph = Paragraph::new(format!(" {}", &*CONFIG.connection.instance)).left_aligned();
-        f.render_widget(paragraph, rect);
+        self.render_instance(f, rect);
 
         self.tabs.render(f, rect);
     }
@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use ratatui::prelude::*;
+
+use crate::{
+    action::{Action, Operation, UpdateAction},
+    app::Ctx,
+    client::ClientError,
+};
+
+use super::components::{
+    input::{InputOutcome, InputPopup},
+    Component,
+};
+
+/// Asks for a 2FA code for accounts that have it enabled.
+pub struct TotpPrompt {
+    input: InputPopup,
+    /// Without a token there's nothing to fall back to, so cancelling quits.
+    required: bool,
+    ctx: Arc<Ctx>,
+}
+
+impl TotpPrompt {
+    pub fn new(ctx: Arc<Ctx>, required: bool) -> Self {
+        let mut input = InputPopup::new("Two-factor authentication code");
+        input.set_message(Some(
+            Line::raw("Enter the code from your authenticator app.").dim(),
+        ));
+
+        ctx.send_action(Action::SwitchToInputMode);
+
+        Self {
+            input,
+            required,
+            ctx,
+        }
+    }
+
+    pub fn set_error(&mut self, error: &ClientError) {
+        self.input.set_message(Some(Line::styled(
+            format!("Login failed: {error}"),
+            Style::new().red(),
+        )));
+    }
+
+    fn submit(&self, totp_2fa_token: String) {
+        let ctx = Arc::clone(&self.ctx);
+        tokio::task::spawn(async move {
+            match ctx.api.submit_totp(totp_2fa_token).await {
+                Ok(()) => ctx.send_update_action(UpdateAction::LoggedIn),
+                Err(e) => ctx.send_update_action(UpdateAction::Error(Operation::Login, e)),
+            }
+        });
+    }
+}
+
+impl Component for TotpPrompt {
+    fn handle_actions(&mut self, action: Action) {
+        let Action::Input(key) = action else {
+            return;
+        };
+
+        match self.input.handle_key(key) {
+            InputOutcome::Submitted(code) => {
+                self.input
+                    .set_message(Some(Line::raw("Logging in...").dim()));
+                self.submit(code);
+            }
+            InputOutcome::Cancelled if self.required => self.ctx.send_action(Action::ForceQuit),
+            InputOutcome::Cancelled => {
+                self.ctx.send_update_action(UpdateAction::CloseTotpPrompt);
+            }
+            InputOutcome::Pending => (),
+        }
+
+        self.ctx.send_action(Action::Render);
+    }
+
+    fn render(&mut self, f: &mut Frame, rect: Rect) {
+        self.input.render(f, rect);
+    }
+}
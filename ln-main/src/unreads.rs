@@ -0,0 +1,68 @@
+//! Keeps the unread counter in the top bar up to date.
+
+use std::{sync::Arc, time::Duration};
+
+use ln_config::CONFIG;
+
+use crate::{
+    action::{Operation, UpdateAction},
+    app::Ctx,
+};
+
+/// Checks for unreads every `poll_interval` seconds for as long as `ctx` is in use.
+///
+/// Each check made while the user is idle doubles the interval, up to
+/// `max_poll_interval`, and the next key press brings it back down.
+pub fn spawn_poller(ctx: Arc<Ctx>) {
+    let base_interval = Duration::from_secs(CONFIG.unreads.poll_interval);
+    if ctx.is_anonymous() || base_interval.is_zero() {
+        return;
+    }
+    let max_interval = Duration::from_secs(CONFIG.unreads.max_poll_interval).max(base_interval);
+
+    tokio::task::spawn(async move {
+        // Let the first page load before anything else.
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        let mut interval = base_interval;
+        let mut failing = false;
+        // The context was replaced once nobody listens to it anymore.
+        while !ctx.update_tx.is_closed() {
+            // Until the 2FA code is entered, logging in is all that works.
+            if !ctx.api.needs_totp() {
+                match ctx.api.unread_count().await {
+                    Ok(unread_count) => {
+                        failing = false;
+                        ctx.send_update_action(UpdateAction::UpdateUnreadsCount(unread_count));
+                    }
+                    // Only the first failure in a row is reported, so an
+                    // unreachable instance doesn't bring back a dismissed error.
+                    Err(e) if !failing => {
+                        failing = true;
+                        ctx.send_update_action(UpdateAction::Error(Operation::FetchUnreadCount, e));
+                    }
+                    Err(_) => (),
+                }
+            }
+
+            interval = if ctx.idle_for() < interval {
+                base_interval
+            } else {
+                (interval * 2).min(max_interval)
+            };
+
+            tokio::select! {
+                _ = tokio::time::sleep(interval) => (),
+                // Coming back shouldn't mean waiting out the longer interval.
+                _ = ctx.activity.notified(), if interval > base_interval => (),
+            }
+        }
+    });
+}
+
+pub async fn fetch_unread_count(ctx: Arc<Ctx>) {
+    match ctx.api.unread_count().await {
+        Ok(unread_count) => ctx.send_update_action(UpdateAction::UpdateUnreadsCount(unread_count)),
+        Err(e) => ctx.send_update_action(UpdateAction::Error(Operation::FetchUnreadCount, e)),
+    }
+}